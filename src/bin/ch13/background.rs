@@ -0,0 +1,156 @@
+use std::f64::consts::PI;
+
+use crate::{
+    pattern::{
+        uv::{cube_uv, face_from_point},
+        ImageTexture, UvPattern,
+    },
+    tuple::{Color, Point, Tuple, Vector},
+};
+
+/// What a ray sees when it leaves the scene without hitting anything.
+#[derive(Debug)]
+pub enum Background {
+    Color(Color),
+    /// Blends from `bottom` straight down to `top` straight up.
+    Gradient {
+        bottom: Color,
+        top: Color,
+    },
+    /// One image per face in `CubeFace` order, each seen from inside the cube.
+    CubeMap([Box<dyn UvPattern>; 6]),
+    /// A latitude/longitude panorama centred on the -z direction.
+    Equirectangular(ImageTexture),
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Color(Color::BLACK)
+    }
+}
+
+impl Background {
+    pub fn color_for(&self, direction: &Vector) -> Color {
+        let d = direction.normalize();
+        match self {
+            Background::Color(color) => *color,
+            Background::Gradient { bottom, top } => {
+                let t = (d.y + 1.0) / 2.0;
+                *bottom * (1.0 - t) + *top * t
+            }
+            Background::CubeMap(faces) => {
+                let scale = d.x.abs().max(d.y.abs()).max(d.z.abs());
+                let p = Point::new(d.x / scale, d.y / scale, d.z / scale);
+                let face = face_from_point(&p);
+                let (u, v) = cube_uv(face, &p);
+                // the faces are viewed from inside, which mirrors them
+                faces[face.index()].uv_pattern_at(1.0 - u, v)
+            }
+            Background::Equirectangular(image) => {
+                let u = 0.5 + d.x.atan2(-d.z) / (2.0 * PI);
+                let v = 1.0 - d.y.clamp(-1.0, 1.0).acos() / PI;
+                image.uv_pattern_at(u, v)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        canvas::Canvas,
+        pattern::{
+            image::Filter,
+            texture_map::{UvAlignCheck, UvCheckers},
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn default_background_is_black() {
+        let bg = Background::default();
+        assert_eq!(bg.color_for(&Vector::new(0.0, 0.0, 1.0)), Color::BLACK);
+    }
+
+    #[test]
+    fn gradient_runs_from_bottom_to_top() {
+        let bg = Background::Gradient {
+            bottom: Color::WHITE,
+            top: Color::new(0.0, 0.0, 1.0),
+        };
+        assert_eq!(bg.color_for(&Vector::new(0.0, -1.0, 0.0)), Color::WHITE);
+        assert_eq!(
+            bg.color_for(&Vector::new(0.0, 1.0, 0.0)),
+            Color::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            bg.color_for(&Vector::new(0.0, 0.0, 5.0)),
+            Color::new(0.5, 0.5, 1.0)
+        );
+    }
+
+    #[test]
+    fn cube_map_background_picks_face_by_direction() {
+        let face = |c: f64| -> Box<dyn UvPattern> {
+            Box::new(UvCheckers::new(1.0, 1.0, Color::new(c, c, c), Color::BLACK))
+        };
+        let bg = Background::CubeMap([
+            face(0.1),
+            face(0.2),
+            face(0.3),
+            face(0.4),
+            face(0.5),
+            face(0.6),
+        ]);
+        assert_eq!(
+            bg.color_for(&Vector::new(-2.0, 0.5, 0.3)),
+            Color::new(0.1, 0.1, 0.1)
+        );
+        assert_eq!(
+            bg.color_for(&Vector::new(0.0, -3.0, 0.0)),
+            Color::new(0.6, 0.6, 0.6)
+        );
+    }
+
+    #[test]
+    fn cube_map_faces_are_not_mirrored_from_inside() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let align = || -> Box<dyn UvPattern> {
+            Box::new(UvAlignCheck::new(
+                Color::WHITE,
+                red,
+                Color::BLACK,
+                Color::BLACK,
+                Color::BLACK,
+            ))
+        };
+        let bg = Background::CubeMap([align(), align(), align(), align(), align(), align()]);
+        // looking down -z with +x to the right, the upper left corner is at -x
+        assert_eq!(bg.color_for(&Vector::new(-0.9, 0.9, -1.0)), red);
+    }
+
+    #[test]
+    fn equirectangular_background_centres_on_negative_z() {
+        let mut canvas = Canvas::new(4, 2);
+        canvas.write(1, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.write(2, 0, Color::new(0.0, 1.0, 0.0));
+        canvas.write(2, 1, Color::new(0.0, 0.0, 1.0));
+        let mut image = ImageTexture::new(canvas);
+        image.filter = Filter::Nearest;
+        let bg = Background::Equirectangular(image);
+        assert_eq!(
+            bg.color_for(&Vector::new(-0.1, 0.5, -1.0)),
+            Color::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            bg.color_for(&Vector::new(0.1, 0.5, -1.0)),
+            Color::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            bg.color_for(&Vector::new(0.1, -0.5, -1.0)),
+            Color::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(bg.color_for(&Vector::new(0.0, 0.5, 1.0)), Color::BLACK);
+    }
+}
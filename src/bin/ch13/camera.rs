@@ -1,8 +1,8 @@
 use crate::{
     canvas::Canvas,
     matrix::Matrix,
-    ray::Ray,
-    tuple::{Point, Tuple},
+    ray::{Differentials, Ray},
+    tuple::{Point, Tuple, Vector},
     world::World,
     DEFAULT_REFLECTION_COUNT,
 };
@@ -40,17 +40,31 @@ impl Camera {
     }
 
     pub fn ray_for_pixel(&self, px: i32, py: i32) -> Ray {
-        let xoffset = (px as f64 + 0.5) * self.pixel_size;
-        let yoffset = (py as f64 + 0.5) * self.pixel_size;
+        let inverse = self.transform.inverse();
+        let origin = inverse * Point::new(0.0, 0.0, 0.0);
+        let (px, py) = (px as f64, py as f64);
+        // rays through the neighbouring pixels share the origin
+        let direction = self.direction_through(&inverse, origin, px, py);
+
+        Ray::new(origin, direction).with_differentials(Some(Differentials {
+            rx_origin: origin,
+            rx_direction: self.direction_through(&inverse, origin, px + 1.0, py),
+            ry_origin: origin,
+            ry_direction: self.direction_through(&inverse, origin, px, py + 1.0),
+        }))
+    }
+
+    /// Direction from `origin` through the centre of pixel (px, py), given the
+    /// inverse of the camera transform.
+    fn direction_through(&self, inverse: &Matrix<4>, origin: Point, px: f64, py: f64) -> Vector {
+        let xoffset = (px + 0.5) * self.pixel_size;
+        let yoffset = (py + 0.5) * self.pixel_size;
 
         let world_x = self.half_width - xoffset;
         let world_y = self.half_height - yoffset;
 
-        let pixel = self.transform.inverse() * Point::new(world_x, world_y, -1.0);
-        let origin = self.transform.inverse() * Point::new(0.0, 0.0, 0.0);
-        let direction = (pixel - origin).normalize();
-
-        Ray::new(origin, direction)
+        let pixel = *inverse * Point::new(world_x, world_y, -1.0);
+        (pixel - origin).normalize()
     }
 }
 
@@ -113,6 +127,19 @@ mod tests {
         assert_eq!(r.origin, Point::new(0.0, 0.0, 0.0));
         assert_eq!(r.direction, Vector::new(0.0, 0.0, -1.0));
     }
+    #[test]
+    fn ray_carries_neighbouring_pixel_rays() {
+        let c = Camera::new(201, 101, FRAC_PI_2);
+        let r = c.ray_for_pixel(100, 50);
+        let d = r.differentials.unwrap();
+        let right = c.ray_for_pixel(101, 50);
+        let below = c.ray_for_pixel(100, 51);
+        assert_eq!(d.rx_origin, right.origin);
+        assert_eq!(d.rx_direction, right.direction);
+        assert_eq!(d.ry_origin, below.origin);
+        assert_eq!(d.ry_direction, below.direction);
+    }
+
     #[test]
     fn constructing_ray_through_corner_of_canvas() {
         let c = Camera::new(201, 101, FRAC_PI_2);
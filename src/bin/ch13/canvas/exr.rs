@@ -0,0 +1,179 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use crate::tuple::Color;
+
+use super::Canvas;
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+/// Version 2, single part, scanline image, short attribute names.
+const VERSION: [u8; 4] = [2, 0, 0, 0];
+const PIXEL_TYPE_FLOAT: i32 = 2;
+type Channel = (&'static str, fn(&Color) -> f64);
+
+/// Channels are stored in alphabetical order of their names.
+const CHANNELS: [Channel; 3] = [("B", |c| c.blue), ("G", |c| c.green), ("R", |c| c.red)];
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+fn window(canvas: &Canvas) -> Vec<u8> {
+    [0, 0, canvas.width as i32 - 1, canvas.height as i32 - 1]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+/// Writes `canvas` as an uncompressed OpenEXR image with 32-bit float B, G
+/// and R channels, one scanline per block.
+pub fn write_exr<W: Write>(canvas: &Canvas, mut writer: W) -> io::Result<()> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&MAGIC);
+    header.extend_from_slice(&VERSION);
+
+    let mut channels = Vec::new();
+    for (name, _) in CHANNELS.iter() {
+        channels.extend_from_slice(name.as_bytes());
+        channels.push(0);
+        channels.extend_from_slice(&PIXEL_TYPE_FLOAT.to_le_bytes());
+        // linear flag and three reserved bytes, then x and y sampling
+        channels.extend_from_slice(&[0, 0, 0, 0]);
+        channels.extend_from_slice(&1i32.to_le_bytes());
+        channels.extend_from_slice(&1i32.to_le_bytes());
+    }
+    channels.push(0);
+    attribute(&mut header, "channels", "chlist", &channels);
+    attribute(&mut header, "compression", "compression", &[0]);
+    attribute(&mut header, "dataWindow", "box2i", &window(canvas));
+    attribute(&mut header, "displayWindow", "box2i", &window(canvas));
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(
+        &mut header,
+        "pixelAspectRatio",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(
+        &mut header,
+        "screenWindowWidth",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    header.push(0);
+
+    let line_size = canvas.width * 3 * 4;
+    let block_size = 8 + line_size;
+    let first_block = header.len() + canvas.height * 8;
+    writer.write_all(&header)?;
+    for y in 0..canvas.height {
+        writer.write_all(&((first_block + y * block_size) as u64).to_le_bytes())?;
+    }
+
+    for (y, row) in canvas.pixels.chunks(canvas.width.max(1)).enumerate() {
+        let mut block = Vec::with_capacity(block_size);
+        block.extend_from_slice(&(y as i32).to_le_bytes());
+        block.extend_from_slice(&(line_size as i32).to_le_bytes());
+        // each block holds the whole row of one channel before the next
+        for (_, component) in CHANNELS.iter() {
+            for pixel in row {
+                block.extend_from_slice(&(component(pixel) as f32).to_le_bytes());
+            }
+        }
+        writer.write_all(&block)?;
+    }
+    writer.flush()
+}
+
+impl Canvas {
+    pub fn to_exr(&self, filename: &str) -> io::Result<()> {
+        write_exr(self, BufWriter::new(File::create(filename)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use super::*;
+
+    fn encode(canvas: &Canvas) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_exr(canvas, &mut out).unwrap();
+        out
+    }
+
+    fn i32_at(bytes: &[u8], pos: usize) -> i32 {
+        i32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+    }
+
+    fn f32_at(bytes: &[u8], pos: usize) -> f32 {
+        f32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+    }
+
+    /// Position just past the header's terminating null byte.
+    fn header_end(exr: &[u8]) -> usize {
+        let mut pos = 8;
+        while exr[pos] != 0 {
+            let name_end = pos + exr[pos..].iter().position(|&b| b == 0).unwrap();
+            let kind_end = name_end + 1 + exr[name_end + 1..].iter().position(|&b| b == 0).unwrap();
+            let size = i32_at(exr, kind_end + 1) as usize;
+            pos = kind_end + 5 + size;
+        }
+        pos + 1
+    }
+
+    #[test]
+    fn exr_starts_with_magic_and_version() {
+        let exr = encode(&Canvas::new(2, 2));
+        assert_eq!(&exr[..4], &MAGIC);
+        assert_eq!(&exr[4..8], &VERSION);
+    }
+
+    #[test]
+    fn exr_header_describes_image() {
+        let exr = encode(&Canvas::new(4, 3));
+        let find = |needle: &[u8]| exr.windows(needle.len()).position(|w| w == needle);
+        assert!(find(b"channels\0chlist\0").is_some());
+        assert!(find(b"compression\0compression\0\x01\0\0\0\0").is_some());
+        let data_window = find(b"dataWindow\0box2i\0").unwrap() + 21;
+        assert_eq!(i32_at(&exr, data_window + 8), 3);
+        assert_eq!(i32_at(&exr, data_window + 12), 2);
+    }
+
+    #[test]
+    fn exr_offsets_point_at_scanlines() {
+        let exr = encode(&Canvas::new(2, 3));
+        let table = header_end(&exr);
+        let block_size = 8 + 2 * 3 * 4;
+        for y in 0..3 {
+            let offset =
+                u64::from_le_bytes(exr[table + y * 8..table + y * 8 + 8].try_into().unwrap());
+            assert_eq!(offset as usize, table + 3 * 8 + y * block_size);
+            assert_eq!(i32_at(&exr, offset as usize), y as i32);
+            assert_eq!(i32_at(&exr, offset as usize + 4), 2 * 3 * 4);
+        }
+        assert_eq!(exr.len(), table + 3 * 8 + 3 * block_size);
+    }
+
+    #[test]
+    fn exr_keeps_values_above_one() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write(1, 0, Color::new(1.9, 0.5, 12.0));
+        let exr = encode(&canvas);
+        let block = header_end(&exr) + 8 + 8;
+        // blue row, then green, then red
+        assert_eq!(f32_at(&exr, block + 4), 12.0);
+        assert_eq!(f32_at(&exr, block + 12), 0.5);
+        assert_eq!(f32_at(&exr, block + 20), 1.9);
+        assert_eq!(f32_at(&exr, block + 16), 0.0);
+    }
+}
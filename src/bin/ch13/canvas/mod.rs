@@ -1,11 +1,13 @@
-use std::{
-    fs::File,
-    io::{self, BufWriter},
-};
+pub mod exr;
+pub mod pfm;
+pub mod png;
+pub mod ppm;
 
-use io::Write;
+use std::{io, path::Path};
 
 use super::tuple::Color;
+use png::PngFormat;
+#[derive(Debug, Clone)]
 pub struct Canvas {
     pub width: usize,
     pub height: usize,
@@ -29,20 +31,22 @@ impl Canvas {
         self.pixels[(self.width * y) + x]
     }
 
-    pub fn to_ppm(&self, filename: &str) -> io::Result<()> {
-        let f = File::create(filename)?;
-        let mut w = BufWriter::new(f);
-        let header = format!("P3\n{} {}\n255", self.width, self.height);
-        w.write_all(header.as_bytes())?;
-        for (i, pixel) in self.pixels.iter().enumerate() {
-            if i % self.width == 0 {
-                w.write_all(b"\n")?;
-            }
-            w.write_all((*pixel * 255f64).to_string().as_bytes())?;
-            w.write_all(b" ")?;
+    /// Writes the canvas in the format named by the file's extension.
+    pub fn save(&self, filename: &str) -> io::Result<()> {
+        let extension = Path::new(filename)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("ppm") => self.to_ppm(filename),
+            Some("png") => self.to_png(filename, PngFormat::Rgb8),
+            Some("pfm") => self.to_pfm(filename),
+            Some("exr") => self.to_exr(filename),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unsupported image format",
+            )),
         }
-
-        Ok(())
     }
 }
 
@@ -67,4 +71,10 @@ mod tests {
         c.write(x, y, red);
         assert_eq!(c.pixel_at(x, y), red);
     }
+
+    #[test]
+    fn saving_with_unknown_extension_is_an_error() {
+        let err = Canvas::new(1, 1).save("scene.bmp").err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
 }
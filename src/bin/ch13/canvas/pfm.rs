@@ -0,0 +1,196 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+};
+
+use crate::tuple::Color;
+
+use super::Canvas;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Reads a colour (`PF`) or greyscale (`Pf`) portable float map.
+///
+/// Values are linear and unclamped, so this is the loader for HDR images.
+/// A negative scale in the header marks little-endian data.
+pub fn read_pfm<R: Read>(mut reader: R) -> io::Result<Canvas> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+
+    // the header is three whitespace separated tokens after the magic
+    let mut fields = vec![];
+    let mut pos = 0;
+    while fields.len() < 4 {
+        while bytes.get(pos).is_some_and(|c| c.is_ascii_whitespace()) {
+            pos += 1;
+        }
+        let start = pos;
+        while bytes.get(pos).is_some_and(|c| !c.is_ascii_whitespace()) {
+            pos += 1;
+        }
+        if start == pos {
+            return Err(invalid("PFM header is truncated"));
+        }
+        fields.push(String::from_utf8_lossy(&bytes[start..pos]).into_owned());
+    }
+    // exactly one whitespace byte separates the header from the raster
+    pos += 1;
+
+    let channels = match fields[0].as_str() {
+        "PF" => 3,
+        "Pf" => 1,
+        _ => return Err(invalid("not a PF or Pf PFM file")),
+    };
+    let number = |s: &str| s.parse().map_err(|_| invalid("bad number in PFM header"));
+    let width: usize = number(&fields[1])?;
+    let height: usize = number(&fields[2])?;
+    if width == 0 || height == 0 {
+        return Err(invalid("PFM image has no pixels"));
+    }
+    let scale: f64 = fields[3]
+        .parse()
+        .map_err(|_| invalid("bad scale in PFM header"))?;
+    let little_endian = scale < 0.0;
+
+    let size = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(channels * 4))
+        .ok_or_else(|| invalid("PFM dimensions are too large"))?;
+    let raster = pos
+        .checked_add(size)
+        .and_then(|end| bytes.get(pos..end))
+        .ok_or_else(|| invalid("PFM raster is truncated"))?;
+    let samples: Vec<f64> = raster
+        .chunks(4)
+        .map(|b| {
+            let b = [b[0], b[1], b[2], b[3]];
+            let value = if little_endian {
+                f32::from_le_bytes(b)
+            } else {
+                f32::from_be_bytes(b)
+            };
+            value as f64
+        })
+        .collect();
+
+    // rows are stored bottom to top
+    let mut canvas = Canvas::new(width, height);
+    for (i, px) in samples.chunks(channels).enumerate() {
+        let x = i % width;
+        let y = height - 1 - i / width;
+        let color = if channels == 3 {
+            Color::new(px[0], px[1], px[2])
+        } else {
+            Color::new(px[0], px[0], px[0])
+        };
+        canvas.write(x, y, color);
+    }
+
+    Ok(canvas)
+}
+
+/// Writes `canvas` as a little-endian colour PFM, keeping values above 1.
+pub fn write_pfm<W: Write>(canvas: &Canvas, mut writer: W) -> io::Result<()> {
+    write!(writer, "PF\n{} {}\n-1.0\n", canvas.width, canvas.height)?;
+    let mut raster = Vec::with_capacity(canvas.pixels.len() * 12);
+    for row in canvas.pixels.chunks(canvas.width.max(1)).rev() {
+        for pixel in row {
+            for component in [pixel.red, pixel.green, pixel.blue] {
+                raster.extend_from_slice(&(component as f32).to_le_bytes());
+            }
+        }
+    }
+    writer.write_all(&raster)?;
+    writer.flush()
+}
+
+impl Canvas {
+    pub fn to_pfm(&self, filename: &str) -> io::Result<()> {
+        write_pfm(self, BufWriter::new(File::create(filename)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pfm(header: &str, values: &[f32], little_endian: bool) -> Vec<u8> {
+        let mut bytes = header.as_bytes().to_vec();
+        for v in values {
+            if little_endian {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            } else {
+                bytes.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn reading_colour_pfm_flips_rows() {
+        let data = pfm("PF\n1 2\n-1.0\n", &[0.0, 0.5, 1.0, 4.0, 2.0, 0.25], true);
+        let canvas = read_pfm(data.as_slice()).unwrap();
+        assert_eq!(canvas.width, 1);
+        assert_eq!(canvas.height, 2);
+        assert_eq!(canvas.pixel_at(0, 1), Color::new(0.0, 0.5, 1.0));
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(4.0, 2.0, 0.25));
+    }
+
+    #[test]
+    fn reading_big_endian_greyscale_pfm() {
+        let data = pfm("Pf\n2 1\n1.0\n", &[3.5, 0.125], false);
+        let canvas = read_pfm(data.as_slice()).unwrap();
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(3.5, 3.5, 3.5));
+        assert_eq!(canvas.pixel_at(1, 0), Color::new(0.125, 0.125, 0.125));
+    }
+
+    #[test]
+    fn wrong_magic_is_an_error() {
+        let data = pfm("P6\n1 1\n-1.0\n", &[0.0, 0.0, 0.0], true);
+        let err = read_pfm(data.as_slice()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn truncated_pfm_is_an_error() {
+        let data = pfm("PF\n2 2\n-1.0\n", &[0.0, 0.0, 0.0], true);
+        let err = read_pfm(data.as_slice()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn empty_or_oversized_pfm_is_an_error() {
+        let huge = format!("PF\n{} {}\n-1.0\n", usize::MAX, 2);
+        for header in ["PF\n0 0\n-1.0\n", "PF\n3 0\n-1.0\n", huge.as_str()] {
+            let err = read_pfm(header.as_bytes()).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn writing_pfm_stores_rows_bottom_up() {
+        let mut canvas = Canvas::new(1, 2);
+        canvas.write(0, 0, Color::new(1.9, 0.5, 0.0));
+        let mut out = Vec::new();
+        write_pfm(&canvas, &mut out).unwrap();
+        assert_eq!(
+            out,
+            pfm("PF\n1 2\n-1.0\n", &[0.0, 0.0, 0.0, 1.9, 0.5, 0.0], true)
+        );
+    }
+
+    #[test]
+    fn written_pfm_reads_back_unclamped() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.write(0, 0, Color::new(12.5, 1.9, -0.25));
+        canvas.write(2, 1, Color::new(0.125, 3.0, 100.0));
+        let mut out = Vec::new();
+        write_pfm(&canvas, &mut out).unwrap();
+        let read = read_pfm(out.as_slice()).unwrap();
+        assert_eq!(read.pixel_at(0, 0), Color::new(12.5, 1.9, -0.25));
+        assert_eq!(read.pixel_at(2, 1), Color::new(0.125, 3.0, 100.0));
+        assert_eq!(read.pixel_at(1, 1), Color::BLACK);
+    }
+}
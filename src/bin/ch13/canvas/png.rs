@@ -0,0 +1,286 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use crate::tuple::Color;
+
+use super::Canvas;
+
+/// Sample layout of a PNG image. Alpha, when present, is always opaque.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PngFormat {
+    Rgb8,
+    Rgba8,
+    Rgb16,
+    Rgba16,
+}
+
+impl PngFormat {
+    fn bit_depth(&self) -> u8 {
+        match self {
+            PngFormat::Rgb8 | PngFormat::Rgba8 => 8,
+            PngFormat::Rgb16 | PngFormat::Rgba16 => 16,
+        }
+    }
+
+    fn color_type(&self) -> u8 {
+        match self {
+            PngFormat::Rgb8 | PngFormat::Rgb16 => 2,
+            PngFormat::Rgba8 | PngFormat::Rgba16 => 6,
+        }
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.color_type() == 6
+    }
+}
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Largest payload of a single stored deflate block.
+const MAX_STORED: usize = 65535;
+/// Largest data field a PNG chunk may carry.
+const MAX_CHUNK: usize = 0x7fff_ffff;
+
+/// CRC-32 as used by PNG chunks (reflected polynomial 0xedb88320).
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Adler-32 checksum that ends a zlib stream.
+pub fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before b could overflow
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// Wraps `data` in a zlib stream of uncompressed deflate blocks.
+pub fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Writes one chunk; `data` must be no longer than `MAX_CHUNK`.
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    let mut crc_input = kind.to_vec();
+    crc_input.extend_from_slice(data);
+    writer.write_all(&crc_input)?;
+    writer.write_all(&crc32(&crc_input).to_be_bytes())
+}
+
+/// Filter-free scanlines: each row starts with filter type 0.
+fn scanlines(canvas: &Canvas, format: PngFormat) -> Vec<u8> {
+    let mut out = Vec::new();
+    for row in canvas.pixels.chunks(canvas.width.max(1)) {
+        out.push(0);
+        for pixel in row {
+            let Color { red, green, blue } = *pixel;
+            let mut samples = vec![red, green, blue];
+            if format.has_alpha() {
+                samples.push(1.0);
+            }
+            for sample in samples {
+                let sample = sample.clamp(0.0, 1.0);
+                if format.bit_depth() == 8 {
+                    out.push((sample * 255.0).round() as u8);
+                } else {
+                    out.extend_from_slice(&((sample * 65535.0).round() as u16).to_be_bytes());
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Writes `canvas` as a PNG in the given sample layout.
+pub fn write_png<W: Write>(canvas: &Canvas, format: PngFormat, mut writer: W) -> io::Result<()> {
+    writer.write_all(&SIGNATURE)?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(canvas.width as u32).to_be_bytes());
+    header.extend_from_slice(&(canvas.height as u32).to_be_bytes());
+    // compression, filter and interlace methods are all 0
+    header.extend_from_slice(&[format.bit_depth(), format.color_type(), 0, 0, 0]);
+    write_chunk(&mut writer, b"IHDR", &header)?;
+
+    // a decoder joins consecutive IDAT chunks back into one stream
+    let data = zlib_stored(&scanlines(canvas, format));
+    for part in data.chunks(MAX_CHUNK) {
+        write_chunk(&mut writer, b"IDAT", part)?;
+    }
+    write_chunk(&mut writer, b"IEND", &[])?;
+    writer.flush()
+}
+
+impl Canvas {
+    pub fn to_png(&self, filename: &str, format: PngFormat) -> io::Result<()> {
+        write_png(self, format, BufWriter::new(File::create(filename)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+
+    use super::*;
+
+    /// Splits a PNG into its chunks, checking each CRC on the way.
+    fn chunks(png: &[u8]) -> Vec<(String, Vec<u8>)> {
+        assert_eq!(&png[..8], &SIGNATURE);
+        let mut pos = 8;
+        let mut out = Vec::new();
+        while pos < png.len() {
+            let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let body = &png[pos + 4..pos + 8 + len];
+            let crc = u32::from_be_bytes(png[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(body));
+            out.push((
+                String::from_utf8(body[..4].to_vec()).unwrap(),
+                body[4..].to_vec(),
+            ));
+            pos += 12 + len;
+        }
+        out
+    }
+
+    /// Undoes `zlib_stored`, checking the block headers and checksum.
+    fn inflate_stored(stream: &[u8]) -> Vec<u8> {
+        assert_eq!(&stream[..2], &[0x78, 0x01]);
+        let mut pos = 2;
+        let mut out = Vec::new();
+        loop {
+            let last = stream[pos] & 1 == 1;
+            let len = u16::from_le_bytes([stream[pos + 1], stream[pos + 2]]);
+            let nlen = u16::from_le_bytes([stream[pos + 3], stream[pos + 4]]);
+            assert_eq!(len, !nlen);
+            out.extend_from_slice(&stream[pos + 5..pos + 5 + len as usize]);
+            pos += 5 + len as usize;
+            if last {
+                break;
+            }
+        }
+        assert_eq!(stream[pos..].to_vec(), adler32(&out).to_be_bytes().to_vec());
+        out
+    }
+
+    fn encode(canvas: &Canvas, format: PngFormat) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_png(canvas, format, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn crc32_matches_known_values() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn adler32_matches_known_values() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(&[]), 1);
+        assert_eq!(adler32(&vec![0xff; 100_000]), 0x149a_302c);
+    }
+
+    #[test]
+    fn large_data_spans_several_stored_blocks() {
+        let data: Vec<u8> = (0..150_000).map(|i| (i % 251) as u8).collect();
+        let stream = zlib_stored(&data);
+        assert_eq!(inflate_stored(&stream), data);
+        assert_eq!(stream.len(), 2 + 3 * 5 + data.len() + 4);
+    }
+
+    #[test]
+    fn png_has_header_data_and_end_chunks() {
+        let png = encode(&Canvas::new(3, 2), PngFormat::Rgb8);
+        let chunks = chunks(&png);
+        let kinds: Vec<&str> = chunks.iter().map(|c| c.0.as_str()).collect();
+        assert_eq!(kinds, vec!["IHDR", "IDAT", "IEND"]);
+        assert_eq!(chunks[0].1, vec![0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        assert!(chunks[2].1.is_empty());
+    }
+
+    #[test]
+    fn eight_bit_rgb_scanlines() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write(0, 0, Color::new(1.0, 0.5, 0.0));
+        canvas.write(1, 0, Color::new(2.0, -1.0, 0.2));
+        let chunks = chunks(&encode(&canvas, PngFormat::Rgb8));
+        assert_eq!(
+            inflate_stored(&chunks[1].1),
+            vec![0, 255, 128, 0, 255, 0, 51]
+        );
+    }
+
+    #[test]
+    fn sixteen_bit_rgba_scanlines() {
+        let mut canvas = Canvas::new(1, 2);
+        canvas.write(0, 0, Color::new(1.0, 0.5, 0.0));
+        let chunks = chunks(&encode(&canvas, PngFormat::Rgba16));
+        assert_eq!(chunks[0].1[8..10], [16, 6]);
+        assert_eq!(
+            inflate_stored(&chunks[1].1),
+            vec![
+                0, 0xff, 0xff, 0x80, 0x00, 0x00, 0x00, 0xff, 0xff, //
+                0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff,
+            ]
+        );
+    }
+
+    #[test]
+    fn sixteen_bit_rgb_scanlines() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write(0, 0, Color::new(1.0, 0.5, 0.0));
+        canvas.write(1, 0, Color::new(2.0, -1.0, 0.2));
+        let chunks = chunks(&encode(&canvas, PngFormat::Rgb16));
+        assert_eq!(chunks[0].1[8..10], [16, 2]);
+        assert_eq!(
+            inflate_stored(&chunks[1].1),
+            vec![
+                0, 0xff, 0xff, 0x80, 0x00, 0x00, 0x00, //
+                0xff, 0xff, 0x00, 0x00, 0x33, 0x33,
+            ]
+        );
+    }
+
+    #[test]
+    fn eight_bit_rgba_is_opaque() {
+        let chunks = chunks(&encode(&Canvas::new(1, 1), PngFormat::Rgba8));
+        assert_eq!(chunks[0].1[8..10], [8, 6]);
+        assert_eq!(inflate_stored(&chunks[1].1), vec![0, 0, 0, 0, 255]);
+    }
+}
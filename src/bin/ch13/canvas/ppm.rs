@@ -0,0 +1,362 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+};
+
+use crate::tuple::Color;
+
+use super::Canvas;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Walks the ASCII part of a PPM file, skipping whitespace and `#` comments.
+struct Tokens<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn next_token(&mut self) -> io::Result<&'a str> {
+        loop {
+            match self.bytes.get(self.pos) {
+                None => return Err(invalid("unexpected end of PPM data")),
+                Some(b'#') => {
+                    while !matches!(self.bytes.get(self.pos), None | Some(b'\n')) {
+                        self.pos += 1;
+                    }
+                }
+                Some(c) if c.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+        let start = self.pos;
+        while matches!(self.bytes.get(self.pos), Some(c) if !c.is_ascii_whitespace() && *c != b'#')
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .map_err(|_| invalid("PPM header is not ASCII"))
+    }
+
+    fn next_number(&mut self) -> io::Result<usize> {
+        self.next_token()?
+            .parse()
+            .map_err(|_| invalid("expected a number in PPM data"))
+    }
+}
+
+/// Reads a plain (P3) or raw (P6) PPM image into a canvas.
+pub fn read_ppm<R: Read>(mut reader: R) -> io::Result<Canvas> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    let mut tokens = Tokens {
+        bytes: &bytes,
+        pos: 0,
+    };
+
+    let binary = match tokens.next_token() {
+        Ok("P3") => false,
+        Ok("P6") => true,
+        _ => return Err(invalid("not a P3 or P6 PPM file")),
+    };
+    let width = tokens.next_number()?;
+    let height = tokens.next_number()?;
+    if width == 0 || height == 0 {
+        return Err(invalid("PPM image has no pixels"));
+    }
+    let max = tokens.next_number()?;
+    if max == 0 || max > 65535 {
+        return Err(invalid("PPM max value must be between 1 and 65535"));
+    }
+    let scale = max as f64;
+
+    let count = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(3))
+        .ok_or_else(|| invalid("PPM dimensions are too large"))?;
+    // every sample takes at least one byte, so the rest of the file bounds the count
+    let mut samples = Vec::with_capacity(count.min(bytes.len() - tokens.pos));
+    if binary {
+        // exactly one whitespace byte separates the header from the raster
+        let start = tokens.pos + 1;
+        let size = if max < 256 { 1 } else { 2 };
+        let raster = count
+            .checked_mul(size)
+            .and_then(|len| bytes.get(start..start.checked_add(len)?))
+            .ok_or_else(|| invalid("PPM raster is truncated"))?;
+        for chunk in raster.chunks(size) {
+            let value = chunk.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+            samples.push(value);
+        }
+    } else {
+        for _ in 0..count {
+            let value = tokens.next_number()?;
+            if value > max {
+                return Err(invalid("PPM sample exceeds the max value"));
+            }
+            samples.push(value);
+        }
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    for (pixel, rgb) in canvas.pixels.iter_mut().zip(samples.chunks(3)) {
+        *pixel = Color::new(
+            rgb[0] as f64 / scale,
+            rgb[1] as f64 / scale,
+            rgb[2] as f64 / scale,
+        );
+    }
+
+    Ok(canvas)
+}
+
+/// Longest line a plain PPM may contain.
+const MAX_LINE: usize = 70;
+
+/// A colour component scaled to 0..=255, clamped and rounded.
+fn to_byte(component: f64) -> u8 {
+    (component.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Writes `canvas` as a plain (P3) PPM, one row per line wrapped at 70 columns.
+pub fn write_ppm<W: Write>(canvas: &Canvas, mut writer: W) -> io::Result<()> {
+    write!(writer, "P3\n{} {}\n255\n", canvas.width, canvas.height)?;
+    for row in canvas.pixels.chunks(canvas.width.max(1)) {
+        let mut line = String::new();
+        for pixel in row {
+            for component in [pixel.red, pixel.green, pixel.blue] {
+                let value = to_byte(component).to_string();
+                if line.len() + 1 + value.len() > MAX_LINE {
+                    writeln!(writer, "{}", line)?;
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&value);
+            }
+        }
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()
+}
+
+/// Writes `canvas` as a binary (P6) PPM with one byte per component.
+pub fn write_ppm_binary<W: Write>(canvas: &Canvas, mut writer: W) -> io::Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", canvas.width, canvas.height)?;
+    let bytes: Vec<u8> = canvas
+        .pixels
+        .iter()
+        .flat_map(|p| [to_byte(p.red), to_byte(p.green), to_byte(p.blue)])
+        .collect();
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+impl Canvas {
+    pub fn to_ppm(&self, filename: &str) -> io::Result<()> {
+        write_ppm(self, BufWriter::new(File::create(filename)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reading_file_with_wrong_magic_number() {
+        let ppm = "P32\n1 1\n255\n0 0 0\n";
+        let err = read_ppm(ppm.as_bytes()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reading_ppm_returns_canvas_of_right_size() {
+        let ppm = "P3\n10 2\n255\n".to_string() + &"0 0 0\n".repeat(20);
+        let canvas = read_ppm(ppm.as_bytes()).unwrap();
+        assert_eq!(canvas.width, 10);
+        assert_eq!(canvas.height, 2);
+    }
+
+    #[test]
+    fn reading_pixel_data_from_ppm() {
+        let ppm = "P3\n4 3\n255\n\
+                   255 127 0  0 127 255  127 255 0  255 255 255\n\
+                   0 0 0  255 0 0  0 255 0  0 0 255\n\
+                   255 255 0  0 255 255  255 0 255  127 127 127\n";
+        let canvas = read_ppm(ppm.as_bytes()).unwrap();
+        let examples = vec![
+            (0, 0, Color::new(1.0, 0.49804, 0.0)),
+            (1, 0, Color::new(0.0, 0.49804, 1.0)),
+            (2, 0, Color::new(0.49804, 1.0, 0.0)),
+            (3, 0, Color::new(1.0, 1.0, 1.0)),
+            (0, 1, Color::new(0.0, 0.0, 0.0)),
+            (1, 1, Color::new(1.0, 0.0, 0.0)),
+            (2, 1, Color::new(0.0, 1.0, 0.0)),
+            (3, 1, Color::new(0.0, 0.0, 1.0)),
+            (0, 2, Color::new(1.0, 1.0, 0.0)),
+            (1, 2, Color::new(0.0, 1.0, 1.0)),
+            (2, 2, Color::new(1.0, 0.0, 1.0)),
+            (3, 2, Color::new(0.49804, 0.49804, 0.49804)),
+        ];
+        for (x, y, color) in examples {
+            assert_eq!(canvas.pixel_at(x, y), color);
+        }
+    }
+
+    #[test]
+    fn ppm_parsing_ignores_comment_lines() {
+        let ppm = "P3\n# this is a comment\n2 1\n# this, too\n255\n\
+                   # another comment\n255 255 255\n# oh, no, comments in the pixel data!\n\
+                   255 0 255\n";
+        let canvas = read_ppm(ppm.as_bytes()).unwrap();
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(canvas.pixel_at(1, 0), Color::new(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn ppm_parsing_allows_rgb_triple_to_span_lines() {
+        let ppm = "P3\n1 1\n255\n51\n153\n\n204\n";
+        let canvas = read_ppm(ppm.as_bytes()).unwrap();
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(0.2, 0.6, 0.8));
+    }
+
+    #[test]
+    fn ppm_parsing_respects_scale_setting() {
+        let ppm = "P3\n2 2\n100\n100 100 100  50 50 50\n75 50 25  0 0 0\n";
+        let canvas = read_ppm(ppm.as_bytes()).unwrap();
+        assert_eq!(canvas.pixel_at(0, 1), Color::new(0.75, 0.5, 0.25));
+    }
+
+    #[test]
+    fn reading_binary_ppm() {
+        let mut ppm = b"P6\n# raw\n2 1\n255\n".to_vec();
+        ppm.extend_from_slice(&[255, 0, 51, 0, 102, 255]);
+        let canvas = read_ppm(ppm.as_slice()).unwrap();
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 0.0, 0.2));
+        assert_eq!(canvas.pixel_at(1, 0), Color::new(0.0, 0.4, 1.0));
+    }
+
+    #[test]
+    fn reading_sixteen_bit_binary_ppm() {
+        let mut ppm = b"P6 1 1 65535\n".to_vec();
+        ppm.extend_from_slice(&[0xff, 0xff, 0x00, 0x00, 0x80, 0x00]);
+        let canvas = read_ppm(ppm.as_slice()).unwrap();
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 0.0, 0.50001));
+    }
+
+    #[test]
+    fn truncated_binary_ppm_is_an_error() {
+        let ppm = b"P6\n2 1\n255\n\x00\x00\x00";
+        let err = read_ppm(&ppm[..]).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn malformed_ppm_headers_are_errors() {
+        let huge = format!("P6\n{} {}\n255\n", usize::MAX, usize::MAX);
+        let headers = [
+            "P3\n0 1\n255\n",
+            "P3\n1 0\n255\n",
+            huge.as_str(),
+            "P3\n1 1\n100\n0 101 0\n",
+        ];
+        for ppm in headers {
+            let err = read_ppm(ppm.as_bytes()).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn non_ascii_token_is_reported_as_such() {
+        let ppm = b"P3\n1 \xff\n255\n0 0 0\n";
+        let err = read_ppm(&ppm[..]).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "PPM header is not ASCII");
+    }
+
+    fn write_to_string(canvas: &Canvas) -> String {
+        let mut out = Vec::new();
+        write_ppm(canvas, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn constructing_ppm_header() {
+        let ppm = write_to_string(&Canvas::new(5, 3));
+        let lines: Vec<&str> = ppm.lines().take(3).collect();
+        assert_eq!(lines, vec!["P3", "5 3", "255"]);
+    }
+
+    #[test]
+    fn constructing_ppm_pixel_data() {
+        let mut canvas = Canvas::new(5, 3);
+        canvas.write(0, 0, Color::new(1.5, 0.0, 0.0));
+        canvas.write(2, 1, Color::new(0.0, 0.5, 0.0));
+        canvas.write(4, 2, Color::new(-0.5, 0.0, 1.0));
+        let ppm = write_to_string(&canvas);
+        let lines: Vec<&str> = ppm.lines().skip(3).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "255 0 0 0 0 0 0 0 0 0 0 0 0 0 0",
+                "0 0 0 0 0 0 0 128 0 0 0 0 0 0 0",
+                "0 0 0 0 0 0 0 0 0 0 0 0 0 0 255",
+            ]
+        );
+    }
+
+    #[test]
+    fn splitting_long_lines_in_ppm() {
+        let mut canvas = Canvas::new(10, 2);
+        for p in canvas.pixels.iter_mut() {
+            *p = Color::new(1.0, 0.8, 0.6);
+        }
+        let ppm = write_to_string(&canvas);
+        let lines: Vec<&str> = ppm.lines().skip(3).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+                "153 255 204 153 255 204 153 255 204 153 255 204 153",
+                "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+                "153 255 204 153 255 204 153 255 204 153 255 204 153",
+            ]
+        );
+    }
+
+    #[test]
+    fn ppm_ends_with_newline() {
+        assert!(write_to_string(&Canvas::new(5, 3)).ends_with('\n'));
+    }
+
+    #[test]
+    fn writing_binary_ppm() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.write(0, 0, Color::new(1.0, 0.5, 0.0));
+        canvas.write(1, 0, Color::new(2.0, -1.0, 0.2));
+        let mut out = Vec::new();
+        write_ppm_binary(&canvas, &mut out).unwrap();
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend_from_slice(&[255, 128, 0, 255, 0, 51]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn written_ppm_reads_back() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.write(1, 0, Color::new(1.0, 0.2, 0.4));
+        canvas.write(2, 1, Color::new(0.0, 0.6, 1.0));
+        let mut plain = Vec::new();
+        write_ppm(&canvas, &mut plain).unwrap();
+        let mut binary = Vec::new();
+        write_ppm_binary(&canvas, &mut binary).unwrap();
+        for bytes in [plain, binary] {
+            let read = read_ppm(bytes.as_slice()).unwrap();
+            assert_eq!(read.pixel_at(1, 0), Color::new(1.0, 0.2, 0.4));
+            assert_eq!(read.pixel_at(2, 1), Color::new(0.0, 0.6, 1.0));
+        }
+    }
+}
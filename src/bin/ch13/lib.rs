@@ -1,11 +1,14 @@
 pub mod camera;
 pub use camera::*;
+mod background;
 mod canvas;
 mod lights;
 mod materials;
 mod matrix;
+mod noise;
 mod pattern;
 mod ray;
+mod sampling;
 mod shape;
 mod transformations;
 mod tuple;
@@ -1,4 +1,9 @@
-use crate::tuple::{Color, Point};
+use std::f64::consts::PI;
+
+use crate::{
+    canvas::Canvas,
+    tuple::{Color, Point, Tuple, Vector},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PointLight {
@@ -14,9 +19,108 @@ impl PointLight {
         }
     }
 }
+/// Light arriving from every direction, read from an equirectangular image.
+///
+/// The image is laid out like `Background::Equirectangular`. Directions are
+/// importance sampled in proportion to the brightness of each pixel.
+#[derive(Debug, Clone)]
+pub struct EnvironmentLight {
+    pub image: Canvas,
+    /// Multiplies the radiance read from the image.
+    pub intensity: f64,
+    /// Shadow rays traced per shaded point.
+    pub samples: usize,
+    /// Cumulative distribution over rows, `height + 1` entries.
+    marginal: Vec<f64>,
+    /// Cumulative distribution over the pixels of each row.
+    conditional: Vec<Vec<f64>>,
+}
+
+fn luminance(c: Color) -> f64 {
+    0.2126 * c.red + 0.7152 * c.green + 0.0722 * c.blue
+}
+
+/// Running sum of `weights` normalised to end at one; uniform if they are all zero.
+fn cdf(weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    let mut cdf = vec![0.0];
+    let mut sum = 0.0;
+    for (i, w) in weights.iter().enumerate() {
+        sum += w;
+        cdf.push(if total > 0.0 {
+            sum / total
+        } else {
+            (i + 1) as f64 / weights.len() as f64
+        });
+    }
+    cdf
+}
+
+/// The bin `u` falls in, how far through it, and the bin's probability.
+fn sample_cdf(cdf: &[f64], u: f64) -> (usize, f64, f64) {
+    let bins = cdf.len() - 1;
+    let i = cdf.partition_point(|&c| c <= u).clamp(1, bins) - 1;
+    let p = cdf[i + 1] - cdf[i];
+    let t = if p > 0.0 { (u - cdf[i]) / p } else { 0.5 };
+    (i, t.clamp(0.0, 1.0), p)
+}
+
+impl EnvironmentLight {
+    /// Panics if `image` has no pixels, since there would be nothing to sample.
+    pub fn new(image: Canvas) -> Self {
+        let (width, height) = (image.width, image.height);
+        assert!(width > 0 && height > 0, "environment image is empty");
+        let mut row_weights = Vec::with_capacity(height);
+        let mut conditional = Vec::with_capacity(height);
+        for y in 0..height {
+            // rows near the poles cover less of the sphere
+            let sin_phi = ((y as f64 + 0.5) / height as f64 * PI).sin();
+            let weights: Vec<f64> = (0..width)
+                .map(|x| luminance(image.pixel_at(x, y)).max(0.0) * sin_phi)
+                .collect();
+            row_weights.push(weights.iter().sum());
+            conditional.push(cdf(&weights));
+        }
+
+        Self {
+            marginal: cdf(&row_weights),
+            conditional,
+            image,
+            intensity: 1.0,
+            samples: 64,
+        }
+    }
+
+    /// Picks a direction for the unit square point (`u1`, `u2`), returning
+    /// it with its radiance and probability density per steradian.
+    pub fn sample(&self, u1: f64, u2: f64) -> (Vector, Color, f64) {
+        let (y, ty, p_row) = sample_cdf(&self.marginal, u1);
+        let (x, tx, p_col) = sample_cdf(&self.conditional[y], u2);
+
+        let u = (x as f64 + tx) / self.image.width as f64;
+        let v = (y as f64 + ty) / self.image.height as f64;
+        let phi = v * PI;
+        let azimuth = (u - 0.5) * 2.0 * PI;
+        let direction = Vector::new(
+            phi.sin() * azimuth.sin(),
+            phi.cos(),
+            -phi.sin() * azimuth.cos(),
+        );
+
+        let sin_phi = phi.sin().max(1e-6);
+        let pdf = p_row * p_col * (self.image.width * self.image.height) as f64
+            / (2.0 * PI * PI * sin_phi);
+        let radiance = self.image.pixel_at(x, y) * self.intensity;
+
+        (direction, radiance, pdf)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::tuple::{Color, Point, Tuple};
+    use float_cmp::approx_eq;
+
+    use crate::{sampling::hammersley, tuple::Color};
 
     use super::*;
 
@@ -28,4 +132,59 @@ mod tests {
         assert_eq!(light.position, position);
         assert_eq!(light.intensity, intensity);
     }
+
+    #[test]
+    #[should_panic(expected = "environment image is empty")]
+    fn empty_environment_image_is_rejected() {
+        EnvironmentLight::new(Canvas::new(0, 0));
+    }
+
+    #[test]
+    fn uniform_environment_pdf_covers_the_sphere() {
+        let mut image = Canvas::new(8, 4);
+        image.pixels = vec![Color::WHITE; 32];
+        let env = EnvironmentLight::new(image);
+        // averaging 1/pdf estimates the area of the unit sphere
+        let n = 4096;
+        let area: f64 = (0..n)
+            .map(|i| {
+                let (u1, u2) = hammersley(i, n);
+                1.0 / env.sample(u1, u2).2
+            })
+            .sum::<f64>()
+            / n as f64;
+        assert!(approx_eq!(f64, area, 4.0 * PI, epsilon = 0.05));
+    }
+
+    #[test]
+    fn sampling_favours_bright_pixels() {
+        let mut image = Canvas::new(4, 2);
+        image.write(2, 0, Color::new(10.0, 10.0, 10.0));
+        let env = EnvironmentLight::new(image);
+        for i in 0..16 {
+            let (u1, u2) = hammersley(i, 16);
+            let (direction, radiance, _) = env.sample(u1, u2);
+            assert_eq!(radiance, Color::new(10.0, 10.0, 10.0));
+            assert!(direction.y > 0.0 && direction.x >= 0.0 && direction.z <= 0.0);
+        }
+    }
+
+    #[test]
+    fn sampled_directions_are_unit_vectors() {
+        let mut image = Canvas::new(3, 3);
+        image.write(1, 1, Color::WHITE);
+        image.write(0, 2, Color::new(0.0, 0.0, 5.0));
+        let env = EnvironmentLight::new(image);
+        for i in 0..8 {
+            let (u1, u2) = hammersley(i, 8);
+            let (direction, _, pdf) = env.sample(u1, u2);
+            assert!(approx_eq!(
+                f64,
+                direction.magnitude(),
+                1.0,
+                epsilon = 0.00003
+            ));
+            assert!(pdf > 0.0);
+        }
+    }
 }
@@ -1,12 +1,15 @@
 #![feature(generic_const_exprs)]
 #![feature(assert_matches)]
+mod background;
 mod camera;
 mod canvas;
 mod lights;
 mod materials;
 mod matrix;
+mod noise;
 mod pattern;
 mod ray;
+mod sampling;
 mod shape;
 mod transformations;
 mod tuple;
@@ -21,6 +24,7 @@ use shape::{Cube, Plane, Shape, Sphere};
 use std::{
     cell::RefCell,
     cmp::Ordering,
+    env,
     f64::consts::{FRAC_PI_2, FRAC_PI_3, FRAC_PI_4, FRAC_PI_6},
     rc::Rc,
 };
@@ -154,7 +158,11 @@ fn main() {
 
     let canvas = render(camera, world, DEFAULT_REFLECTION_COUNT);
 
-    canvas.to_ppm("ch13_table_scene.ppm").unwrap();
+    // the extension picks the format, e.g. `cargo run --bin ch13 scene.exr`
+    let filename = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "ch13_table_scene.png".to_string());
+    canvas.save(&filename).unwrap();
 }
 
 fn float_cmp(first: &f64, second: &f64) -> std::cmp::Ordering {
@@ -0,0 +1,232 @@
+use std::fmt::Debug;
+
+use crate::{
+    noise::Perlin,
+    pattern::{ImageTexture, UvPattern},
+    shape::Shape,
+    tuple::{Point, Tuple, Vector},
+};
+
+use super::surface_tangent;
+
+/// Perturbs the shading normal of a surface.
+pub trait Bump: Debug {
+    /// The bumped version of the world space `normalv` at world space `point`.
+    fn perturb(&self, object: &dyn Shape, point: &Point, normalv: &Vector) -> Vector;
+}
+
+/// Step used to differentiate height fields.
+const DELTA: f64 = 0.0001;
+
+/// Tilts `normalv` against the slope of a height field given in object space.
+fn bump_height<F: Fn(&Point) -> f64>(
+    object: &dyn Shape,
+    point: &Point,
+    normalv: &Vector,
+    height: F,
+) -> Vector {
+    let p = object.world_to_object(point);
+    let slope = |offset: Vector| (height(&(p + offset)) - height(&(p - offset))) / (2.0 * DELTA);
+    let gradient = Vector::new(
+        slope(Vector::new(DELTA, 0.0, 0.0)),
+        slope(Vector::new(0.0, DELTA, 0.0)),
+        slope(Vector::new(0.0, 0.0, DELTA)),
+    );
+    // gradients transform like normals
+    let gradient = object.get_transform().inverse().transpose() * gradient;
+    let along_surface = gradient - *normalv * gradient.dot(*normalv);
+
+    (*normalv - along_surface).normalize()
+}
+
+/// Ripples from `sin(x) * sin(z)` in object space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Waves {
+    pub amplitude: f64,
+    pub frequency: f64,
+}
+
+impl Waves {
+    pub fn new(amplitude: f64, frequency: f64) -> Self {
+        Self {
+            amplitude,
+            frequency,
+        }
+    }
+}
+
+impl Bump for Waves {
+    fn perturb(&self, object: &dyn Shape, point: &Point, normalv: &Vector) -> Vector {
+        bump_height(object, point, normalv, |p| {
+            self.amplitude * (self.frequency * p.x).sin() * (self.frequency * p.z).sin()
+        })
+    }
+}
+
+/// Irregular bumps from Perlin noise in object space.
+#[derive(Debug, Clone)]
+pub struct NoiseBump {
+    pub amplitude: f64,
+    /// Noise features per unit of object space.
+    pub scale: f64,
+    pub perlin: Perlin,
+}
+
+impl NoiseBump {
+    pub fn new(amplitude: f64, scale: f64) -> Self {
+        Self {
+            amplitude,
+            scale,
+            perlin: Perlin::default(),
+        }
+    }
+}
+
+impl Bump for NoiseBump {
+    fn perturb(&self, object: &dyn Shape, point: &Point, normalv: &Vector) -> Vector {
+        bump_height(object, point, normalv, |p| {
+            self.amplitude * self.perlin.noise(&(*p * self.scale))
+        })
+    }
+}
+
+/// Tangent space normals read from an image at the shape's own UV coordinates.
+///
+/// Red runs along the tangent, green along increasing v and blue out of the
+/// surface, each stored as `(n + 1) / 2`.
+#[derive(Debug)]
+pub struct NormalMap {
+    pub texture: ImageTexture,
+    /// Scales the tilt read from the map; 0 leaves the surface flat.
+    pub strength: f64,
+}
+
+impl NormalMap {
+    pub fn new(texture: ImageTexture) -> Self {
+        Self {
+            texture,
+            strength: 1.0,
+        }
+    }
+}
+
+impl Bump for NormalMap {
+    fn perturb(&self, object: &dyn Shape, point: &Point, normalv: &Vector) -> Vector {
+        let local_point = object.world_to_object(point);
+        let (u, v) = object.local_uv_at(&local_point);
+        let c = self.texture.uv_pattern_at(u, v);
+
+        let tangent = surface_tangent(object, point, normalv);
+        let bitangent = tangent.cross(*normalv);
+        let x = (c.red * 2.0 - 1.0) * self.strength;
+        let y = (c.green * 2.0 - 1.0) * self.strength;
+        let z = c.blue * 2.0 - 1.0;
+
+        (tangent * x + bitangent * y + *normalv * z).normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use crate::{
+        canvas::Canvas,
+        matrix::Matrix,
+        shape::{Plane, Sphere},
+        transformations::Transformation,
+        tuple::Color,
+    };
+
+    use super::*;
+
+    fn flat_map(color: Color) -> NormalMap {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.write(0, 0, color);
+        NormalMap::new(ImageTexture::new(canvas))
+    }
+
+    #[test]
+    fn waves_leave_crests_flat() {
+        let plane = Plane::new(0);
+        let waves = Waves::new(0.5, std::f64::consts::PI);
+        let n = Vector::new(0.0, 1.0, 0.0);
+        let crest = Point::new(0.5, 0.0, 0.5);
+        assert_eq!(waves.perturb(&plane, &crest, &n), n);
+    }
+
+    #[test]
+    fn waves_tilt_normal_against_slope() {
+        let plane = Plane::new(0);
+        let waves = Waves::new(0.5, std::f64::consts::PI);
+        let n = Vector::new(0.0, 1.0, 0.0);
+        // height rises with x here, so the normal leans back towards -x
+        let perturbed = waves.perturb(&plane, &Point::new(0.0, 0.0, 0.5), &n);
+        assert!(perturbed.x < 0.0);
+        assert!(approx_eq!(f64, perturbed.z, 0.0, epsilon = 0.00003));
+        assert!(approx_eq!(
+            f64,
+            perturbed.magnitude(),
+            1.0,
+            epsilon = 0.00003
+        ));
+    }
+
+    #[test]
+    fn bump_follows_object_transform() {
+        let mut plane = Plane::new(0);
+        plane.transform = Matrix::default().scaling(2.0, 1.0, 2.0);
+        let waves = Waves::new(0.5, std::f64::consts::PI);
+        let n = Vector::new(0.0, 1.0, 0.0);
+        assert_eq!(waves.perturb(&plane, &Point::new(1.0, 0.0, 1.0), &n), n);
+    }
+
+    #[test]
+    fn noise_bump_keeps_normal_in_front_of_surface() {
+        let sphere = Sphere::new(0);
+        let bump = NoiseBump::new(0.2, 4.0);
+        let n = Vector::new(0.0, 0.0, -1.0);
+        let perturbed = bump.perturb(&sphere, &Point::new(0.0, 0.0, -1.0), &n);
+        assert!(perturbed.dot(n) > 0.5);
+        assert!(approx_eq!(
+            f64,
+            perturbed.magnitude(),
+            1.0,
+            epsilon = 0.00003
+        ));
+    }
+
+    #[test]
+    fn neutral_normal_map_leaves_normal_alone() {
+        let plane = Plane::new(0);
+        let map = flat_map(Color::new(0.5, 0.5, 1.0));
+        let n = Vector::new(0.0, 1.0, 0.0);
+        assert_eq!(map.perturb(&plane, &Point::new(0.3, 0.0, 0.7), &n), n);
+    }
+
+    #[test]
+    fn normal_map_uses_tangent_frame() {
+        let plane = Plane::new(0);
+        let n = Vector::new(0.0, 1.0, 0.0);
+        let along_u = flat_map(Color::new(1.0, 0.5, 0.5));
+        assert_eq!(
+            along_u.perturb(&plane, &Point::new(0.3, 0.0, 0.7), &n),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+        // the plane's v runs along +z
+        let along_v = flat_map(Color::new(0.5, 1.0, 0.5));
+        assert_eq!(
+            along_v.perturb(&plane, &Point::new(0.3, 0.0, 0.7), &n),
+            Vector::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn zero_strength_normal_map_is_flat() {
+        let plane = Plane::new(0);
+        let mut map = flat_map(Color::new(1.0, 0.2, 0.7));
+        map.strength = 0.0;
+        let n = Vector::new(0.0, 1.0, 0.0);
+        assert_eq!(map.perturb(&plane, &Point::new(0.0, 0.0, 0.0), &n), n);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{pattern::ScalarTexture, shape::Shape, tuple::Point};
+
+use super::Material;
+
+/// A material laid over another, such as clear coat over paint or rust over
+/// metal. `shade_hit` shades both and mixes the results.
+///
+/// The hit itself is worked out from the base material, so the layer's own
+/// `bump` and `refractive_index` are not used: `prepare_computations` fixes
+/// the normal and the refractive indices before any layer is looked at.
+#[derive(Debug)]
+pub struct Layer {
+    pub material: Material,
+    /// How much of the layer shows, from 0 (none) to 1 (fully covers the base).
+    pub weight: f64,
+    /// Varies the weight over the surface, replacing `weight` when set.
+    pub mask: Option<ScalarTexture>,
+}
+
+impl Layer {
+    pub fn new(material: Material, weight: f64) -> Self {
+        Self {
+            material,
+            weight,
+            mask: None,
+        }
+    }
+
+    pub fn masked(material: Material, mask: ScalarTexture) -> Self {
+        Self {
+            material,
+            weight: 1.0,
+            mask: Some(mask),
+        }
+    }
+
+    /// The layer's coverage at `point`, clamped to [0, 1].
+    pub fn weight_at(&self, object: &dyn Shape, point: &Point) -> f64 {
+        let weight = match &self.mask {
+            Some(mask) => mask.value_at_shape(object, point),
+            None => self.weight,
+        };
+        weight.clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pattern::{scalar::Channel, Stripe},
+        shape::Sphere,
+        tuple::{Color, Tuple},
+    };
+
+    use super::*;
+
+    #[test]
+    fn plain_layer_has_constant_weight() {
+        let layer = Layer::new(Material::default(), 0.3);
+        let sphere = Sphere::new(0);
+        assert_eq!(layer.weight_at(&sphere, &Point::new(0.0, 0.0, -1.0)), 0.3);
+    }
+
+    #[test]
+    fn masked_layer_weight_follows_mask() {
+        let mask = ScalarTexture::new(
+            Box::new(Stripe::new(Color::WHITE, Color::BLACK)),
+            Channel::Red,
+        );
+        let layer = Layer::masked(Material::default(), mask);
+        let sphere = Sphere::new(0);
+        assert_eq!(layer.weight_at(&sphere, &Point::new(0.5, 0.0, -0.5)), 1.0);
+        assert_eq!(layer.weight_at(&sphere, &Point::new(-0.5, 0.0, -0.5)), 0.0);
+    }
+
+    #[test]
+    fn weight_is_clamped() {
+        let layer = Layer::new(Material::default(), 1.7);
+        let sphere = Sphere::new(0);
+        assert_eq!(layer.weight_at(&sphere, &Point::new(0.0, 1.0, 0.0)), 1.0);
+    }
+}
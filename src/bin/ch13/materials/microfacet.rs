@@ -0,0 +1,157 @@
+use std::f64::consts::PI;
+
+use crate::tuple::{Color, Tuple, Vector};
+
+use super::Material;
+
+/// Reflectance at normal incidence shared by all non-metals.
+const DIELECTRIC_F0: f64 = 0.04;
+/// Roughness is kept away from zero so the distribution stays finite.
+const MIN_ROUGHNESS: f64 = 0.03;
+
+pub fn distribution_ggx(n_dot_h: f64, alpha: f64) -> f64 {
+    let a2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    a2 / (PI * d * d)
+}
+
+fn schlick_ggx(n_dot_x: f64, k: f64) -> f64 {
+    n_dot_x / (n_dot_x * (1.0 - k) + k)
+}
+
+/// GGX with separate widths along the tangent (`alpha_x`) and bitangent.
+pub fn distribution_anisotropic_ggx(
+    h_dot_n: f64,
+    h_dot_t: f64,
+    h_dot_b: f64,
+    alpha_x: f64,
+    alpha_y: f64,
+) -> f64 {
+    let d = (h_dot_t / alpha_x).powi(2) + (h_dot_b / alpha_y).powi(2) + h_dot_n.powi(2);
+    1.0 / (PI * alpha_x * alpha_y * d * d)
+}
+
+pub fn geometry_smith(n_dot_v: f64, n_dot_l: f64, alpha: f64) -> f64 {
+    let k = alpha / 2.0;
+    schlick_ggx(n_dot_v, k) * schlick_ggx(n_dot_l, k)
+}
+
+pub fn fresnel_schlick(cos_theta: f64, f0: Color) -> Color {
+    f0 + (Color::WHITE - f0) * (1.0 - cos_theta).max(0.0).powi(5)
+}
+
+/// Unit directions at the shaded point that the BRDF is evaluated against.
+pub struct ShadingFrame {
+    pub normalv: Vector,
+    pub eyev: Vector,
+    /// Direction the highlight stretches along, for anisotropic surfaces.
+    pub tangent: Option<Vector>,
+}
+
+/// Diffuse and specular contributions of one light under the GGX model, for
+/// a surface whose roughness at the shaded point is `roughness`.
+///
+/// Light intensity is treated the same way as in Phong: a white Lambertian
+/// surface facing the light reflects all of it, so the BRDF is scaled by π.
+pub fn lighting(
+    material: &Material,
+    roughness: f64,
+    color: Color,
+    intensity: Color,
+    lightv: &Vector,
+    frame: &ShadingFrame,
+) -> (Color, Color) {
+    let ShadingFrame {
+        normalv,
+        eyev,
+        tangent,
+    } = frame;
+    let n_dot_l = normalv.dot(*lightv).max(0.0);
+    let n_dot_v = normalv.dot(*eyev).max(0.0001);
+    let halfway = (*lightv + *eyev).normalize();
+    let n_dot_h = normalv.dot(halfway).max(0.0);
+    let v_dot_h = eyev.dot(halfway).max(0.0);
+
+    let roughness = roughness.clamp(MIN_ROUGHNESS, 1.0);
+    let alpha = roughness * roughness;
+    let metallic = material.metallic.clamp(0.0, 1.0);
+
+    let f0 = Color::new(DIELECTRIC_F0, DIELECTRIC_F0, DIELECTRIC_F0) * (1.0 - metallic)
+        + color * metallic;
+    let f = fresnel_schlick(v_dot_h, f0);
+    let d = match tangent {
+        Some(tangent) => {
+            let aspect = (1.0 - 0.9 * material.anisotropy.clamp(-1.0, 1.0)).sqrt();
+            let bitangent = normalv.cross(*tangent);
+            distribution_anisotropic_ggx(
+                n_dot_h,
+                halfway.dot(*tangent),
+                halfway.dot(bitangent),
+                alpha / aspect,
+                alpha * aspect,
+            )
+        }
+        None => distribution_ggx(n_dot_h, alpha),
+    };
+    let g = geometry_smith(n_dot_v, n_dot_l, alpha);
+
+    let specular = f * (d * g * PI / (4.0 * n_dot_v)) * intensity;
+    let diffuse = (Color::WHITE - f) * color * intensity * ((1.0 - metallic) * n_dot_l);
+
+    (diffuse, specular)
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn ggx_peak_depends_only_on_alpha() {
+        let alpha = 0.5;
+        let d = distribution_ggx(1.0, alpha);
+        assert!(approx_eq!(
+            f64,
+            d,
+            1.0 / (PI * alpha * alpha),
+            epsilon = 0.00003
+        ));
+    }
+
+    #[test]
+    fn smooth_surfaces_concentrate_the_distribution() {
+        assert!(distribution_ggx(1.0, 0.1) > distribution_ggx(1.0, 0.9));
+        assert!(distribution_ggx(0.8, 0.1) < distribution_ggx(0.8, 0.9));
+    }
+
+    #[test]
+    fn anisotropic_ggx_with_equal_widths_is_isotropic() {
+        let h = Vector::new(0.3, 0.2, 0.9).normalize();
+        let iso = distribution_ggx(h.z, 0.4);
+        let aniso = distribution_anisotropic_ggx(h.z, h.x, h.y, 0.4, 0.4);
+        assert!(approx_eq!(f64, iso, aniso, epsilon = 0.00003));
+    }
+
+    #[test]
+    fn fresnel_at_normal_incidence_is_f0() {
+        let f0 = Color::new(0.04, 0.5, 1.0);
+        assert_eq!(fresnel_schlick(1.0, f0), f0);
+    }
+
+    #[test]
+    fn fresnel_at_grazing_angle_is_white() {
+        let f0 = Color::new(0.04, 0.5, 1.0);
+        assert_eq!(fresnel_schlick(0.0, f0), Color::WHITE);
+    }
+
+    #[test]
+    fn smith_term_is_one_head_on() {
+        assert!(approx_eq!(
+            f64,
+            geometry_smith(1.0, 1.0, 0.3),
+            1.0,
+            epsilon = 0.00003
+        ));
+    }
+}
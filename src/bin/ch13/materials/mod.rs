@@ -0,0 +1,775 @@
+pub mod bump;
+pub mod layer;
+pub mod microfacet;
+
+use super::pattern::Pattern;
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    lights::PointLight,
+    pattern::{Footprint, ScalarTexture, Stripe},
+    sampling::orthonormal_basis,
+    shape::{sphere::reflect, Shape},
+    tuple::{Color, Point, Tuple, Vector},
+};
+
+/// Selects the reflectance model `lighting` uses for direct light.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Model {
+    /// Classic Phong using `diffuse`, `specular` and `shininess`.
+    Phong,
+    /// Metallic-roughness microfacet BRDF using `metallic` and `roughness`.
+    MetallicRoughness,
+}
+
+/// How `shade_hit` weights reflected light.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fresnel {
+    /// Schlick's approximation for materials both reflective and transparent.
+    Dielectric,
+    /// Metal with complex refractive index `eta + ik` per colour channel.
+    ///
+    /// Only tints the reflection `reflective` lets through, so a conductor
+    /// needs `reflective > 0` to reflect anything.
+    Conductor { eta: Color, k: Color },
+}
+
+impl Fresnel {
+    pub const GOLD_ETA: Color = Color {
+        red: 0.143,
+        green: 0.374,
+        blue: 1.442,
+    };
+    pub const GOLD_K: Color = Color {
+        red: 3.983,
+        green: 2.385,
+        blue: 1.603,
+    };
+    pub const COPPER_ETA: Color = Color {
+        red: 0.200,
+        green: 0.924,
+        blue: 1.102,
+    };
+    pub const COPPER_K: Color = Color {
+        red: 3.912,
+        green: 2.452,
+        blue: 2.142,
+    };
+    pub const SILVER_ETA: Color = Color {
+        red: 0.155,
+        green: 0.117,
+        blue: 0.138,
+    };
+    pub const SILVER_K: Color = Color {
+        red: 4.828,
+        green: 3.122,
+        blue: 2.147,
+    };
+    pub const GOLD: Self = Self::Conductor {
+        eta: Self::GOLD_ETA,
+        k: Self::GOLD_K,
+    };
+    pub const COPPER: Self = Self::Conductor {
+        eta: Self::COPPER_ETA,
+        k: Self::COPPER_K,
+    };
+    pub const SILVER: Self = Self::Conductor {
+        eta: Self::SILVER_ETA,
+        k: Self::SILVER_K,
+    };
+}
+
+/// A transparent coating whose interference colours the reflection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThinFilm {
+    /// Film thickness in nanometres.
+    pub thickness: f64,
+    /// Refractive index of the film itself.
+    pub ior: f64,
+}
+
+impl ThinFilm {
+    pub fn new(thickness: f64, ior: f64) -> Self {
+        Self { thickness, ior }
+    }
+}
+
+#[derive(Debug)]
+pub struct Material {
+    pub color: Color,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    pub reflective: f64,
+    pub transparency: f64,
+    pub refractive_index: f64,
+    pub pattern: Option<Box<dyn Pattern>>,
+    pub model: Model,
+    pub metallic: f64,
+    pub roughness: f64,
+    pub anisotropy: f64,
+    pub refraction_roughness: f64,
+    /// Weights the reflection; conductors still need `reflective` above zero.
+    pub fresnel: Fresnel,
+    pub thin_film: Option<ThinFilm>,
+    pub emission: Color,
+    pub emission_strength: f64,
+    pub bump: Option<Box<dyn bump::Bump>>,
+    pub reflective_map: Option<ScalarTexture>,
+    pub transparency_map: Option<ScalarTexture>,
+    pub specular_map: Option<ScalarTexture>,
+    pub shininess_map: Option<ScalarTexture>,
+    pub roughness_map: Option<ScalarTexture>,
+    /// Cutout mask; hits where it falls below `cutout_threshold` are skipped.
+    pub opacity_map: Option<ScalarTexture>,
+    pub cutout_threshold: f64,
+    /// Another material blended over this one.
+    pub layer: Option<Box<layer::Layer>>,
+}
+
+impl PartialEq for Material {
+    fn eq(&self, other: &Self) -> bool {
+        self.color.eq(&other.color)
+            && self.ambient.eq(&other.ambient)
+            && self.diffuse.eq(&other.diffuse)
+            && self.specular.eq(&other.specular)
+            && self.shininess.eq(&other.shininess)
+            && self.reflective.eq(&other.reflective)
+            && self.model.eq(&other.model)
+            && self.metallic.eq(&other.metallic)
+            && self.roughness.eq(&other.roughness)
+            && self.anisotropy.eq(&other.anisotropy)
+            && self.refraction_roughness.eq(&other.refraction_roughness)
+            && self.fresnel.eq(&other.fresnel)
+            && self.thin_film.eq(&other.thin_film)
+            && self.emission.eq(&other.emission)
+            && self.emission_strength.eq(&other.emission_strength)
+            && self.cutout_threshold.eq(&other.cutout_threshold)
+    }
+}
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            color: Color::new(1.0, 1.0, 1.0),
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.9,
+            shininess: 200.0,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+            pattern: None,
+            model: Model::Phong,
+            metallic: 0.0,
+            roughness: 0.0,
+            anisotropy: 0.0,
+            refraction_roughness: 0.0,
+            fresnel: Fresnel::Dielectric,
+            thin_film: None,
+            emission: Color::BLACK,
+            emission_strength: 1.0,
+            bump: None,
+            reflective_map: None,
+            transparency_map: None,
+            specular_map: None,
+            shininess_map: None,
+            roughness_map: None,
+            opacity_map: None,
+            cutout_threshold: 0.5,
+            layer: None,
+        }
+    }
+}
+
+impl Material {
+    /// Light given off by the surface itself, independent of any light source.
+    pub fn emitted(&self) -> Color {
+        self.emission * self.emission_strength
+    }
+
+    /// The base colour at `point`, from the pattern if there is one.
+    pub fn color_at(&self, object: &dyn Shape, point: &Point) -> Color {
+        match &self.pattern {
+            Some(p) => p.pattern_at_shape(object, point),
+            None => self.color,
+        }
+    }
+
+    /// The base colour averaged over `footprint`, or a point sample without one.
+    pub fn filtered_color_at(
+        &self,
+        object: &dyn Shape,
+        point: &Point,
+        footprint: &Option<Footprint>,
+    ) -> Color {
+        match (&self.pattern, footprint) {
+            (Some(p), Some(footprint)) => p.pattern_at_footprint(object, point, footprint),
+            _ => self.color_at(object, point),
+        }
+    }
+
+    pub fn reflective_at(&self, object: &dyn Shape, point: &Point) -> f64 {
+        scalar_at(&self.reflective_map, self.reflective, object, point)
+    }
+
+    pub fn transparency_at(&self, object: &dyn Shape, point: &Point) -> f64 {
+        scalar_at(&self.transparency_map, self.transparency, object, point)
+    }
+
+    pub fn specular_at(&self, object: &dyn Shape, point: &Point) -> f64 {
+        scalar_at(&self.specular_map, self.specular, object, point)
+    }
+
+    pub fn shininess_at(&self, object: &dyn Shape, point: &Point) -> f64 {
+        scalar_at(&self.shininess_map, self.shininess, object, point)
+    }
+
+    pub fn roughness_at(&self, object: &dyn Shape, point: &Point) -> f64 {
+        scalar_at(&self.roughness_map, self.roughness, object, point)
+    }
+
+    /// Whether the opacity mask removes the surface at `point` entirely.
+    pub fn is_cut_out(&self, object: &dyn Shape, point: &Point) -> bool {
+        match &self.opacity_map {
+            Some(mask) => mask.value_at_shape(object, point) < self.cutout_threshold,
+            None => false,
+        }
+    }
+}
+
+/// The texture's value at `point` if there is one, otherwise the plain field.
+fn scalar_at(map: &Option<ScalarTexture>, value: f64, object: &dyn Shape, point: &Point) -> f64 {
+    match map {
+        Some(texture) => texture.value_at_shape(object, point),
+        None => value,
+    }
+}
+
+pub fn lighting(
+    material: &Material,
+    object: &dyn Shape,
+    light: &PointLight,
+    point: &Point,
+    eyev: &Vector,
+    normalv: &Vector,
+    in_shadow: bool,
+) -> Color {
+    let color = material.color_at(object, point);
+    let ambient = color * light.intensity * material.ambient;
+    if in_shadow {
+        return ambient;
+    }
+
+    let incoming = IncomingLight::from_light(light, point);
+    ambient + direct_lighting(material, object, color, &incoming, point, eyev, normalv)
+}
+
+/// Light of `intensity` reaching the surface from the unit direction `lightv`.
+pub struct IncomingLight {
+    pub intensity: Color,
+    pub lightv: Vector,
+}
+
+impl IncomingLight {
+    /// What `light` shines onto `point`, ignoring anything in the way.
+    pub fn from_light(light: &PointLight, point: &Point) -> Self {
+        Self {
+            intensity: light.intensity,
+            lightv: (light.position - *point).normalize(),
+        }
+    }
+}
+
+/// Diffuse and specular light arriving as `incoming`, without ambient or
+/// shadowing, for a surface whose colour at `point` is `color`.
+pub fn direct_lighting(
+    material: &Material,
+    object: &dyn Shape,
+    color: Color,
+    incoming: &IncomingLight,
+    point: &Point,
+    eyev: &Vector,
+    normalv: &Vector,
+) -> Color {
+    let intensity = incoming.intensity;
+    let lightv = &incoming.lightv;
+    let effective_color = color * intensity;
+    let light_dot_normal = lightv.dot(*normalv);
+    if light_dot_normal < 0.0 {
+        return Color::BLACK;
+    }
+    let tangent = if material.anisotropy != 0.0 {
+        Some(surface_tangent(object, point, normalv))
+    } else {
+        None
+    };
+    let (diffuse, specular) = if material.model == Model::MetallicRoughness {
+        microfacet::lighting(
+            material,
+            material.roughness_at(object, point),
+            color,
+            intensity,
+            lightv,
+            &microfacet::ShadingFrame {
+                normalv: *normalv,
+                eyev: *eyev,
+                tangent,
+            },
+        )
+    } else if let Some(tangent) = tangent {
+        let diffuse = effective_color * material.diffuse * light_dot_normal;
+        let shininess = material.shininess_at(object, point);
+        let factor = anisotropic_phong(material, shininess, &tangent, lightv, eyev, normalv);
+        let specular = intensity * material.specular_at(object, point) * factor;
+
+        (diffuse, specular)
+    } else {
+        let diffuse = effective_color * material.diffuse * light_dot_normal;
+
+        let reflectv = reflect(-*lightv, *normalv);
+        let reflect_dot_eye = reflectv.dot(*eyev);
+
+        if reflect_dot_eye <= 0.0 {
+            (diffuse, Color::BLACK)
+        } else {
+            let factor = reflect_dot_eye.powf(material.shininess_at(object, point));
+            let specular = intensity * material.specular_at(object, point) * factor;
+
+            (diffuse, specular)
+        }
+    };
+
+    diffuse + specular
+}
+
+/// The shape's tangent at `point`, made perpendicular to the shading normal.
+fn surface_tangent(object: &dyn Shape, point: &Point, normalv: &Vector) -> Vector {
+    let tangent = object.tangent_at(*point);
+    let tangent = tangent - *normalv * normalv.dot(tangent);
+    if tangent.magnitude() < 0.00003 {
+        orthonormal_basis(*normalv).0
+    } else {
+        tangent.normalize()
+    }
+}
+
+/// Ashikhmin-Shirley highlight whose exponents are stretched by `anisotropy`.
+///
+/// The Blinn exponent is four times the Phong one, so with no anisotropy
+/// the lobe matches the isotropic Phong highlight.
+fn anisotropic_phong(
+    material: &Material,
+    shininess: f64,
+    tangent: &Vector,
+    lightv: &Vector,
+    eyev: &Vector,
+    normalv: &Vector,
+) -> f64 {
+    let halfway = (*lightv + *eyev).normalize();
+    let h_dot_n = halfway.dot(*normalv);
+    if h_dot_n <= 0.0 {
+        return 0.0;
+    }
+    let sin2 = 1.0 - h_dot_n * h_dot_n;
+    if sin2 < 1e-12 {
+        return 1.0;
+    }
+
+    let bitangent = normalv.cross(*tangent);
+    let anisotropy = material.anisotropy.clamp(-1.0, 1.0);
+    let nu = 4.0 * shininess * (1.0 - anisotropy);
+    let nv = 4.0 * shininess * (1.0 + anisotropy);
+    let exponent =
+        (nu * halfway.dot(*tangent).powi(2) + nv * halfway.dot(bitangent).powi(2)) / sin2;
+
+    h_dot_n.powf(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::FRAC_1_SQRT_2, f64::consts::FRAC_2_PI};
+
+    use crate::{
+        lights::PointLight,
+        pattern::{scalar::Channel, Solid},
+        ray::{Intersection, Ray},
+        shape::{Plane, Sphere},
+        tuple::{Point, Tuple, Vector},
+    };
+
+    use super::*;
+
+    #[test]
+    fn default_material() {
+        let m = Material::default();
+        assert_eq!(m.color, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(m.ambient, 0.1);
+        assert_eq!(m.diffuse, 0.9);
+        assert_eq!(m.specular, 0.9);
+        assert_eq!(m.shininess, 200.0);
+    }
+
+    #[test]
+    fn lighting_with_eye_between_light_and_surface() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let m = Material::default();
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let in_shadow = false;
+        let result = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &position,
+            &eyev,
+            &normalv,
+            in_shadow,
+        );
+        assert_eq!(result, Color::new(1.9, 1.9, 1.9));
+    }
+
+    #[test]
+    fn specular_map_removes_highlight() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let mut m = Material::default();
+        m.specular_map = Some(
+            ScalarTexture::new(
+                Box::new(Stripe::new(Color::WHITE, Color::BLACK)),
+                Channel::Red,
+            )
+            .with_range(0.0, 0.9),
+        );
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.5, 0.0, -10.0), Color::WHITE);
+        let shiny = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &Point::new(0.5, 0.0, 0.0),
+            &eyev,
+            &normalv,
+            false,
+        );
+        let light = PointLight::new(Point::new(1.5, 0.0, -10.0), Color::WHITE);
+        let matte = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &Point::new(1.5, 0.0, 0.0),
+            &eyev,
+            &normalv,
+            false,
+        );
+        assert_eq!(shiny, Color::new(1.9, 1.9, 1.9));
+        assert_eq!(matte, Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn scalar_accessors_fall_back_to_fields() {
+        let object = Sphere::new(0);
+        let mut m = Material::default();
+        m.reflective = 0.3;
+        m.roughness_map = Some(
+            ScalarTexture::new(Box::new(Solid::new(Color::WHITE)), Channel::Green)
+                .with_range(0.2, 0.6),
+        );
+        let p = Point::new(0.0, 1.0, 0.0);
+        assert_eq!(m.reflective_at(&object, &p), 0.3);
+        assert_eq!(m.transparency_at(&object, &p), 0.0);
+        assert_eq!(m.shininess_at(&object, &p), 200.0);
+        assert_eq!(m.roughness_at(&object, &p), 0.6);
+    }
+
+    #[test]
+    fn lighting_with_eye_offset_by_45() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let m = Material::default();
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eyev = Vector::new(0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let in_shadow = false;
+        let result = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &position,
+            &eyev,
+            &normalv,
+            in_shadow,
+        );
+        assert_eq!(result, Color::new(1.0, 1.0, 1.0));
+    }
+    #[test]
+    fn lighting_with_light_offset_by_45() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let m = Material::default();
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let in_shadow = false;
+        let result = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &position,
+            &eyev,
+            &normalv,
+            in_shadow,
+        );
+        assert_eq!(result, Color::new(0.7364, 0.7364, 0.7364,));
+    }
+    #[test]
+    fn lighting_with_eye_in_reflection_path() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let m = Material::default();
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eyev = Vector::new(0.0, -FRAC_1_SQRT_2, -FRAC_1_SQRT_2);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let in_shadow = false;
+        let result = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &position,
+            &eyev,
+            &normalv,
+            in_shadow,
+        );
+        assert_eq!(result, Color::new(1.6364, 1.6364, 1.6364,));
+    }
+    #[test]
+    fn lighting_with_light_behind_surface() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let m = Material::default();
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::new(1.0, 1.0, 1.0));
+        let in_shadow = false;
+        let result = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &position,
+            &eyev,
+            &normalv,
+            in_shadow,
+        );
+        assert_eq!(result, Color::new(0.1, 0.1, 0.1,));
+    }
+
+    #[test]
+    fn lighting_with_surface_in_shadow() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let m = Material::default();
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::WHITE);
+        let in_shadow = true;
+        let result = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &position,
+            &eyev,
+            &normalv,
+            in_shadow,
+        );
+
+        assert_eq!(result, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn default_reflectivity() {
+        let m = Material::default();
+        assert_eq!(m.reflective, 0.0);
+    }
+
+    #[test]
+    fn default_transparency_and_refractive_index() {
+        let m = Material::default();
+        assert_eq!(m.transparency, 0.0);
+        assert_eq!(m.refractive_index, 1.0);
+    }
+
+    #[test]
+    fn default_refraction_is_smooth() {
+        let m = Material::default();
+        assert_eq!(m.refraction_roughness, 0.0);
+    }
+
+    #[test]
+    fn default_fresnel_is_dielectric() {
+        let m = Material::default();
+        assert_eq!(m.fresnel, Fresnel::Dielectric);
+        assert_eq!(m.thin_film, None);
+    }
+
+    #[test]
+    fn default_model_is_phong() {
+        let m = Material::default();
+        assert_eq!(m.model, Model::Phong);
+        assert_eq!(m.metallic, 0.0);
+        assert_eq!(m.roughness, 0.0);
+    }
+
+    #[test]
+    fn metallic_roughness_lighting_head_on() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let mut m = Material::default();
+        m.model = Model::MetallicRoughness;
+        m.roughness = 1.0;
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::WHITE);
+        let result = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &position,
+            &eyev,
+            &normalv,
+            false,
+        );
+        assert_eq!(result, Color::new(1.07, 1.07, 1.07));
+    }
+
+    #[test]
+    fn metal_reflects_only_its_own_color() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let mut m = Material::default();
+        m.model = Model::MetallicRoughness;
+        m.color = Color::new(1.0, 0.0, 0.0);
+        m.metallic = 1.0;
+        m.roughness = 0.4;
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::WHITE);
+        let result = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &position,
+            &eyev,
+            &normalv,
+            false,
+        );
+        assert!(result.red > 1.0);
+        assert_eq!(result.green, 0.0);
+        assert_eq!(result.blue, 0.0);
+    }
+
+    #[test]
+    fn metallic_roughness_in_shadow_is_ambient_only() {
+        let object = Rc::new(RefCell::new(Sphere::new(0)));
+        let mut m = Material::default();
+        m.model = Model::MetallicRoughness;
+        m.roughness = 0.5;
+        let position = Point::new(0.0, 0.0, 0.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::WHITE);
+        let result = lighting(
+            &m,
+            &*object.borrow(),
+            &light,
+            &position,
+            &eyev,
+            &normalv,
+            true,
+        );
+        assert_eq!(result, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn default_material_emits_nothing() {
+        let m = Material::default();
+        assert_eq!(m.emission, Color::BLACK);
+        assert_eq!(m.emission_strength, 1.0);
+        assert_eq!(m.emitted(), Color::BLACK);
+    }
+
+    #[test]
+    fn emission_is_scaled_by_strength() {
+        let mut m = Material::default();
+        m.emission = Color::new(1.0, 0.5, 0.25);
+        m.emission_strength = 4.0;
+        assert_eq!(m.emitted(), Color::new(4.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn anisotropic_highlight_stretches_along_tangent() {
+        let object = Plane::new(0);
+        let mut m = Material::default();
+        m.shininess = 50.0;
+        m.anisotropy = 0.8;
+        let position = Point::new(0.0, 0.0, 0.0);
+        let normalv = Vector::new(0.0, 1.0, 0.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, 0.0), Color::WHITE);
+        let lit = |eyev: Vector| {
+            lighting(
+                &m,
+                &object,
+                &light,
+                &position,
+                &eyev.normalize(),
+                &normalv,
+                false,
+            )
+        };
+        let along_tangent = lit(Vector::new(0.3, 1.0, 0.0));
+        let across_tangent = lit(Vector::new(0.0, 1.0, 0.3));
+        assert!(along_tangent.red > across_tangent.red + 0.1);
+    }
+
+    #[test]
+    fn anisotropic_microfacet_highlight_stretches_along_tangent() {
+        let object = Plane::new(0);
+        let mut m = Material::default();
+        m.model = Model::MetallicRoughness;
+        m.roughness = 0.3;
+        m.anisotropy = 0.8;
+        let position = Point::new(0.0, 0.0, 0.0);
+        let normalv = Vector::new(0.0, 1.0, 0.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, 0.0), Color::WHITE);
+        let lit = |eyev: Vector| {
+            lighting(
+                &m,
+                &object,
+                &light,
+                &position,
+                &eyev.normalize(),
+                &normalv,
+                false,
+            )
+        };
+        let along_tangent = lit(Vector::new(0.3, 1.0, 0.0));
+        let across_tangent = lit(Vector::new(0.0, 1.0, 0.3));
+        assert!(along_tangent.red > across_tangent.red + 0.1);
+    }
+
+    #[test]
+    fn zero_anisotropy_keeps_phong_highlight() {
+        let object = Plane::new(0);
+        let mut m = Material::default();
+        let position = Point::new(0.0, 0.0, 0.0);
+        let normalv = Vector::new(0.0, 1.0, 0.0);
+        let eyev = Vector::new(0.0, 1.0, 0.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, 0.0), Color::WHITE);
+        let isotropic = lighting(&m, &object, &light, &position, &eyev, &normalv, false);
+        m.anisotropy = 0.5;
+        let anisotropic = lighting(&m, &object, &light, &position, &eyev, &normalv, false);
+        assert_eq!(isotropic, Color::new(1.9, 1.9, 1.9));
+        assert_eq!(anisotropic, isotropic);
+    }
+}
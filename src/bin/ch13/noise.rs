@@ -0,0 +1,196 @@
+use crate::tuple::Point;
+
+/// Improved Perlin gradient noise over a seeded permutation table.
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    permutation: Vec<usize>,
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product of (x, y, z) with one of twelve gradient directions.
+fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 drives a Fisher-Yates shuffle of 0..256
+        let mut state = seed;
+        let mut next = || {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        let mut p: Vec<usize> = (0..256).collect();
+        for i in (1..256).rev() {
+            let j = (next() % (i as u64 + 1)) as usize;
+            p.swap(i, j);
+        }
+        let permutation = p.iter().chain(p.iter()).copied().collect();
+
+        Self { permutation }
+    }
+
+    /// Smooth noise in roughly [-1, 1], zero at every integer lattice point.
+    pub fn noise(&self, point: &Point) -> f64 {
+        let p = &self.permutation;
+        let (xf, yf, zf) = (point.x.floor(), point.y.floor(), point.z.floor());
+        let xi = (xf as i64).rem_euclid(256) as usize;
+        let yi = (yf as i64).rem_euclid(256) as usize;
+        let zi = (zf as i64).rem_euclid(256) as usize;
+        let (x, y, z) = (point.x - xf, point.y - yf, point.z - zf);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let a = p[xi] + yi;
+        let aa = p[a] + zi;
+        let ab = p[a + 1] + zi;
+        let b = p[xi + 1] + yi;
+        let ba = p[b] + zi;
+        let bb = p[b + 1] + zi;
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad(p[ab], x, y - 1.0, z),
+                    grad(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.0),
+                    grad(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+
+    /// Fractional Brownian motion: `octaves` layers of noise, each
+    /// `lacunarity` times finer and `gain` times weaker, kept in [-1, 1].
+    pub fn fbm(&self, point: &Point, octaves: u32, lacunarity: f64, gain: f64) -> f64 {
+        self.octaves(point, octaves, lacunarity, gain, |n| n)
+    }
+
+    /// Like `fbm` but summing absolute values, giving creases in [0, 1].
+    pub fn turbulence(&self, point: &Point, octaves: u32, lacunarity: f64, gain: f64) -> f64 {
+        self.octaves(point, octaves, lacunarity, gain, f64::abs)
+    }
+
+    fn octaves<F: Fn(f64) -> f64>(
+        &self,
+        point: &Point,
+        octaves: u32,
+        lacunarity: f64,
+        gain: f64,
+        shape: F,
+    ) -> f64 {
+        let mut sum = 0.0;
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        for _ in 0..octaves.max(1) {
+            sum += amplitude * shape(self.noise(&(*point * frequency)));
+            total += amplitude;
+            amplitude *= gain;
+            frequency *= lacunarity;
+        }
+        sum / total
+    }
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tuple::Tuple;
+
+    use super::*;
+
+    #[test]
+    fn noise_vanishes_on_lattice_points() {
+        let perlin = Perlin::default();
+        assert_eq!(perlin.noise(&Point::new(0.0, 0.0, 0.0)), 0.0);
+        assert_eq!(perlin.noise(&Point::new(3.0, -7.0, 12.0)), 0.0);
+    }
+
+    #[test]
+    fn noise_is_bounded_and_varies() {
+        let perlin = Perlin::default();
+        let values: Vec<f64> = (0..200)
+            .map(|i| {
+                let t = i as f64 * 0.173;
+                perlin.noise(&Point::new(t, t * 0.7 + 0.3, 1.9 - t * 0.2))
+            })
+            .collect();
+        assert!(values.iter().all(|v| v.abs() <= 1.0));
+        assert!(values.iter().any(|&v| v > 0.1));
+        assert!(values.iter().any(|&v| v < -0.1));
+    }
+
+    #[test]
+    fn same_seed_gives_same_noise() {
+        let p = Point::new(1.3, 2.7, -0.4);
+        assert_eq!(Perlin::new(7).noise(&p), Perlin::new(7).noise(&p));
+        assert_ne!(Perlin::new(7).noise(&p), Perlin::new(8).noise(&p));
+    }
+
+    #[test]
+    fn noise_is_continuous() {
+        let perlin = Perlin::new(3);
+        let a = perlin.noise(&Point::new(0.5, 0.5, 0.5));
+        let b = perlin.noise(&Point::new(0.5001, 0.5, 0.5));
+        assert!((a - b).abs() < 0.001);
+    }
+
+    #[test]
+    fn one_octave_of_fbm_is_plain_noise() {
+        let perlin = Perlin::default();
+        let p = Point::new(0.3, 1.7, -2.2);
+        assert_eq!(perlin.fbm(&p, 1, 2.0, 0.5), perlin.noise(&p));
+        assert_eq!(perlin.turbulence(&p, 1, 2.0, 0.5), perlin.noise(&p).abs());
+    }
+
+    #[test]
+    fn fractal_noise_stays_in_range() {
+        let perlin = Perlin::new(11);
+        for i in 0..100 {
+            let t = i as f64 * 0.37;
+            let p = Point::new(t, -t * 0.5, t * 0.25 + 0.1);
+            assert!(perlin.fbm(&p, 5, 2.0, 0.5).abs() <= 1.0);
+            let turbulence = perlin.turbulence(&p, 5, 2.0, 0.5);
+            assert!((0.0..=1.0).contains(&turbulence));
+        }
+    }
+}
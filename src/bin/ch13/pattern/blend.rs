@@ -0,0 +1,126 @@
+use crate::{
+    matrix::Matrix,
+    tuple::{Color, Point},
+};
+
+use super::Pattern;
+
+/// How much of the second pattern a `Blend` lets through.
+#[derive(Debug)]
+pub enum Mix {
+    /// The same proportion everywhere, 0 for all `a` and 1 for all `b`.
+    Weight(f64),
+    /// Per channel proportions read from a third pattern.
+    Mask(Box<dyn Pattern>),
+}
+
+/// Mixes two patterns, each evaluated in the blend's own pattern space.
+#[derive(Debug)]
+pub struct Blend {
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
+    pub mix: Mix,
+    pub transform: Matrix<4>,
+}
+
+impl Blend {
+    pub fn new(a: Box<dyn Pattern>, b: Box<dyn Pattern>, weight: f64) -> Self {
+        Self {
+            a,
+            b,
+            mix: Mix::Weight(weight),
+            transform: Default::default(),
+        }
+    }
+
+    pub fn masked(a: Box<dyn Pattern>, b: Box<dyn Pattern>, mask: Box<dyn Pattern>) -> Self {
+        Self {
+            a,
+            b,
+            mix: Mix::Mask(mask),
+            transform: Default::default(),
+        }
+    }
+}
+
+impl Pattern for Blend {
+    fn pattern_at(&self, point: &Point) -> Color {
+        let point = self.transform.inverse() * *point;
+        let a = self.a.pattern_at(&point);
+        let b = self.b.pattern_at(&point);
+        match &self.mix {
+            Mix::Weight(w) => a * (1.0 - w) + b * *w,
+            Mix::Mask(mask) => {
+                let m = mask.pattern_at(&point);
+                a * (Color::WHITE - m) + b * m
+            }
+        }
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pattern::{Solid, Stripe},
+        transformations::Transformation,
+        tuple::Tuple,
+    };
+
+    use super::*;
+
+    #[test]
+    fn weighted_blend_mixes_evenly() {
+        let pattern = Blend::new(
+            Box::new(Solid::new(Color::WHITE)),
+            Box::new(Solid::new(Color::new(1.0, 0.0, 0.0))),
+            0.25,
+        );
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.0, 0.0, 0.0)),
+            Color::new(1.0, 0.75, 0.75)
+        );
+    }
+
+    #[test]
+    fn mask_selects_per_point() {
+        let pattern = Blend::masked(
+            Box::new(Solid::new(Color::new(1.0, 0.0, 0.0))),
+            Box::new(Solid::new(Color::new(0.0, 0.0, 1.0))),
+            Box::new(Stripe::new(Color::BLACK, Color::WHITE)),
+        );
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.5, 0.0, 0.0)),
+            Color::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            pattern.pattern_at(&Point::new(1.5, 0.0, 0.0)),
+            Color::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn blended_patterns_keep_their_own_transforms() {
+        let mut vertical = Stripe::new(Color::WHITE, Color::BLACK);
+        vertical.set_transform(Matrix::default().rotation_y(std::f64::consts::FRAC_PI_2));
+        let horizontal = Stripe::new(Color::WHITE, Color::BLACK);
+        let mut pattern = Blend::new(Box::new(horizontal), Box::new(vertical), 0.5);
+        pattern.set_transform(Matrix::default().scaling(2.0, 2.0, 2.0));
+        // once scaled, x = 1.5 is in a white stripe and only z picks the other
+        assert_eq!(
+            pattern.pattern_at(&Point::new(1.5, 0.0, -0.5)),
+            Color::WHITE
+        );
+        assert_eq!(
+            pattern.pattern_at(&Point::new(1.5, 0.0, 0.5)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+    }
+}
@@ -3,17 +3,22 @@ use crate::{
     tuple::{Color, Point},
 };
 
-use super::Pattern;
+use super::{Pattern, Solid};
 
 #[derive(Debug)]
 pub struct Checkerboard {
-    a: Color,
-    b: Color,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
     pub transform: Matrix<4>,
 }
 
 impl Checkerboard {
     pub fn new(a: Color, b: Color) -> Self {
+        Self::nested(Box::new(Solid::new(a)), Box::new(Solid::new(b)))
+    }
+
+    /// Checks filled with other patterns rather than plain colours.
+    pub fn nested(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
         Self {
             a,
             b,
@@ -26,24 +31,24 @@ impl Pattern for Checkerboard {
     fn pattern_at(&self, point: &Point) -> Color {
         let point = self.transform.inverse() * *point;
         if (point.x.floor() + point.y.floor() + point.z.floor()) % 2.0 == 0.0 {
-            self.a
+            self.a.pattern_at(&point)
         } else {
-            self.b
+            self.b.pattern_at(&point)
         }
     }
 
     fn transform(&self) -> Matrix<4> {
-        todo!()
+        self.transform
     }
 
     fn set_transform(&mut self, transform: Matrix<4>) {
-        todo!()
+        self.transform = transform;
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tuple::Tuple;
+    use crate::{pattern::Stripe, transformations::Transformation, tuple::Tuple};
 
     use super::*;
 
@@ -86,4 +91,31 @@ mod tests {
             Color::BLACK
         );
     }
+
+    #[test]
+    fn checkerboard_of_stripes() {
+        let mut stripes = Stripe::new(Color::new(1.0, 0.0, 0.0), Color::new(0.0, 0.0, 1.0));
+        stripes.set_transform(Matrix::default().scaling(0.25, 0.25, 0.25));
+        let pattern = Checkerboard::nested(Box::new(stripes), Box::new(Solid::new(Color::BLACK)));
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.1, 0.0, 0.0)),
+            Color::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.3, 0.0, 0.0)),
+            Color::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(pattern.pattern_at(&Point::new(1.3, 0.0, 0.0)), Color::BLACK);
+    }
+
+    #[test]
+    fn checkerboard_transform_can_be_set() {
+        let mut pattern = Checkerboard::new(Color::WHITE, Color::BLACK);
+        pattern.set_transform(Matrix::default().scaling(2.0, 2.0, 2.0));
+        assert_eq!(
+            pattern.transform(),
+            Matrix::default().scaling(2.0, 2.0, 2.0)
+        );
+        assert_eq!(pattern.pattern_at(&Point::new(1.5, 0.0, 0.0)), Color::WHITE);
+    }
 }
@@ -0,0 +1,110 @@
+use crate::{
+    matrix::Matrix,
+    noise::Perlin,
+    tuple::{Color, Point},
+};
+
+use super::{ColorRamp, Pattern};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fractal {
+    /// Smooth clouds from fractional Brownian motion.
+    Fbm,
+    /// Sharp creased folds from summed absolute noise.
+    Turbulence,
+}
+
+/// Fractal noise coloured through a ramp.
+#[derive(Debug)]
+pub struct FractalNoise {
+    pub ramp: ColorRamp,
+    pub fractal: Fractal,
+    pub octaves: u32,
+    pub lacunarity: f64,
+    pub gain: f64,
+    pub perlin: Perlin,
+    pub transform: Matrix<4>,
+}
+
+impl FractalNoise {
+    pub fn new(a: Color, b: Color, fractal: Fractal) -> Self {
+        Self {
+            ramp: ColorRamp::between(a, b),
+            fractal,
+            octaves: 4,
+            lacunarity: 2.0,
+            gain: 0.5,
+            perlin: Perlin::default(),
+            transform: Default::default(),
+        }
+    }
+
+    /// The noise value at `point` in pattern space, mapped to [0, 1].
+    pub fn value_at(&self, point: &Point) -> f64 {
+        match self.fractal {
+            Fractal::Fbm => {
+                (self
+                    .perlin
+                    .fbm(point, self.octaves, self.lacunarity, self.gain)
+                    + 1.0)
+                    / 2.0
+            }
+            Fractal::Turbulence => {
+                self.perlin
+                    .turbulence(point, self.octaves, self.lacunarity, self.gain)
+            }
+        }
+    }
+}
+
+impl Pattern for FractalNoise {
+    fn pattern_at(&self, point: &Point) -> Color {
+        let point = self.transform.inverse() * *point;
+        let t = self.value_at(&point);
+        self.ramp.color_at(t)
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tuple::Tuple;
+
+    use super::*;
+
+    #[test]
+    fn fbm_is_halfway_on_lattice_points() {
+        let pattern = FractalNoise::new(Color::BLACK, Color::WHITE, Fractal::Fbm);
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.0, 0.0, 0.0)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+    }
+
+    #[test]
+    fn turbulence_starts_from_first_colour() {
+        let pattern = FractalNoise::new(Color::BLACK, Color::WHITE, Fractal::Turbulence);
+        assert_eq!(pattern.pattern_at(&Point::new(0.0, 0.0, 0.0)), Color::BLACK);
+    }
+
+    #[test]
+    fn fractal_noise_varies_between_colours() {
+        let pattern = FractalNoise::new(Color::BLACK, Color::WHITE, Fractal::Fbm);
+        let values: Vec<f64> = (0..100)
+            .map(|i| {
+                let t = i as f64 * 0.19 + 0.1;
+                pattern.pattern_at(&Point::new(t, t * 0.3, -t)).red
+            })
+            .collect();
+        assert!(values.iter().all(|v| (0.0..=1.0).contains(v)));
+        assert!(values.iter().any(|&v| v > 0.55));
+        assert!(values.iter().any(|&v| v < 0.45));
+    }
+}
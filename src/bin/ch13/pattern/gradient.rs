@@ -3,19 +3,49 @@ use crate::{
     tuple::{Color, Point},
 };
 
-use super::Pattern;
+use super::{ColorRamp, Pattern, Solid};
+
+/// The distance a `Gradient` measures progress by; it repeats every unit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientKind {
+    /// Along the x axis.
+    Linear,
+    /// Away from the y axis.
+    Radial,
+    /// Away from the origin.
+    Spherical,
+}
+
+#[derive(Debug)]
+enum Fill {
+    Nested(Box<dyn Pattern>, Box<dyn Pattern>),
+    Ramp(ColorRamp),
+}
 
 #[derive(Debug)]
 pub struct Gradient {
-    a: Color,
-    b: Color,
+    fill: Fill,
+    pub kind: GradientKind,
     pub transform: Matrix<4>,
 }
 impl Gradient {
     pub fn new(a: Color, b: Color) -> Self {
+        Self::nested(Box::new(Solid::new(a)), Box::new(Solid::new(b)))
+    }
+
+    /// Fades from one sub-pattern to the other along x.
+    pub fn nested(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
+        Self {
+            fill: Fill::Nested(a, b),
+            kind: GradientKind::Linear,
+            transform: Default::default(),
+        }
+    }
+
+    pub fn ramp(ramp: ColorRamp) -> Self {
         Self {
-            a,
-            b,
+            fill: Fill::Ramp(ramp),
+            kind: GradientKind::Linear,
             transform: Default::default(),
         }
     }
@@ -23,22 +53,33 @@ impl Gradient {
 impl Pattern for Gradient {
     fn pattern_at(&self, point: &Point) -> Color {
         let point = self.transform.inverse() * *point;
-        let distance = self.b - self.a;
-        let fraction = point.x - point.x.floor();
-        self.a + distance * fraction
+        let distance = match self.kind {
+            GradientKind::Linear => point.x,
+            GradientKind::Radial => (point.x.powi(2) + point.z.powi(2)).sqrt(),
+            GradientKind::Spherical => (point.x.powi(2) + point.y.powi(2) + point.z.powi(2)).sqrt(),
+        };
+        let fraction = distance - distance.floor();
+        match &self.fill {
+            Fill::Nested(a, b) => {
+                let a = a.pattern_at(&point);
+                let b = b.pattern_at(&point);
+                a + (b - a) * fraction
+            }
+            Fill::Ramp(ramp) => ramp.color_at(fraction),
+        }
     }
     fn transform(&self) -> Matrix<4> {
-        todo!()
+        self.transform
     }
 
     fn set_transform(&mut self, transform: Matrix<4>) {
-        todo!()
+        self.transform = transform;
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tuple::Tuple;
+    use crate::{pattern::Stripe, tuple::Tuple};
 
     use super::*;
 
@@ -59,4 +100,50 @@ mod tests {
             Color::new(0.25, 0.25, 0.25)
         );
     }
+
+    #[test]
+    fn gradient_between_patterns() {
+        let pattern = Gradient::nested(
+            Box::new(Solid::new(Color::WHITE)),
+            Box::new(Stripe::new(Color::BLACK, Color::new(1.0, 0.0, 0.0))),
+        );
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.5, 0.0, 0.0)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(
+            pattern.pattern_at(&Point::new(1.5, 0.0, 0.0)),
+            Color::new(1.0, 0.5, 0.5)
+        );
+    }
+
+    #[test]
+    fn gradient_through_ramp() {
+        let pattern = Gradient::ramp(ColorRamp::new(vec![
+            (0.0, Color::BLACK),
+            (0.5, Color::WHITE),
+            (1.0, Color::BLACK),
+        ]));
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.25, 0.0, 0.0)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(pattern.pattern_at(&Point::new(1.5, 0.0, 0.0)), Color::WHITE);
+    }
+
+    #[test]
+    fn radial_and_spherical_gradients() {
+        let mut pattern = Gradient::new(Color::WHITE, Color::BLACK);
+        pattern.kind = GradientKind::Radial;
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.3, 5.0, 0.4)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        pattern.kind = GradientKind::Spherical;
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.0, 0.5, 0.0)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(pattern.pattern_at(&Point::new(0.0, 3.0, 4.0)), Color::WHITE);
+    }
 }
@@ -0,0 +1,302 @@
+use crate::{canvas::Canvas, tuple::Color};
+
+use super::texture_map::UvPattern;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+    /// Blends the two mip levels nearest the lookup's footprint.
+    Trilinear,
+    /// Several trilinear probes along the long axis of a stretched footprint.
+    Anisotropic,
+}
+
+/// What happens to texel coordinates that fall outside the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    Repeat,
+    Clamp,
+}
+
+impl Wrap {
+    fn apply(&self, i: i64, size: usize) -> usize {
+        match self {
+            Wrap::Repeat => i.rem_euclid(size as i64) as usize,
+            Wrap::Clamp => i.clamp(0, size as i64 - 1) as usize,
+        }
+    }
+}
+
+/// Samples a canvas by (u, v); v = 0 is the bottom row of the image.
+#[derive(Debug)]
+pub struct ImageTexture {
+    /// The image followed by successively halved copies, down to 1x1.
+    levels: Vec<Canvas>,
+    pub filter: Filter,
+    pub wrap: Wrap,
+    /// Most probes an anisotropic lookup may take.
+    pub max_anisotropy: usize,
+}
+
+impl ImageTexture {
+    pub fn new(canvas: Canvas) -> Self {
+        Self {
+            levels: mip_pyramid(canvas),
+            filter: Filter::Trilinear,
+            wrap: Wrap::Repeat,
+            max_anisotropy: 8,
+        }
+    }
+
+    pub fn levels(&self) -> &[Canvas] {
+        &self.levels
+    }
+
+    fn texel(&self, level: usize, x: i64, y: i64) -> Color {
+        let canvas = &self.levels[level];
+        if canvas.pixels.is_empty() {
+            return Color::BLACK;
+        }
+        canvas.pixel_at(
+            self.wrap.apply(x, canvas.width),
+            self.wrap.apply(y, canvas.height),
+        )
+    }
+
+    fn nearest(&self, u: f64, v: f64) -> Color {
+        let canvas = &self.levels[0];
+        let x = u * canvas.width as f64;
+        let y = (1.0 - v) * canvas.height as f64;
+        self.texel(0, x.floor() as i64, y.floor() as i64)
+    }
+
+    fn bilinear(&self, level: usize, u: f64, v: f64) -> Color {
+        let canvas = &self.levels[level];
+        // texel centres sit at half-integer coordinates
+        let x = u * canvas.width as f64 - 0.5;
+        let y = (1.0 - v) * canvas.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(level, x0, y0) * (1.0 - tx) + self.texel(level, x0 + 1, y0) * tx;
+        let bottom =
+            self.texel(level, x0, y0 + 1) * (1.0 - tx) + self.texel(level, x0 + 1, y0 + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    /// Bilinear samples from the levels either side of `lod`, blended.
+    fn trilinear(&self, lod: f64, u: f64, v: f64) -> Color {
+        let lod = lod.clamp(0.0, (self.levels.len() - 1) as f64);
+        let lower = lod.floor() as usize;
+        let t = lod - lower as f64;
+        if t == 0.0 {
+            return self.bilinear(lower, u, v);
+        }
+        self.bilinear(lower, u, v) * (1.0 - t) + self.bilinear(lower + 1, u, v) * t
+    }
+}
+
+/// Halves the image repeatedly, averaging each 2x2 block into one texel.
+/// An empty image has nothing to average and stays a single level.
+fn mip_pyramid(canvas: Canvas) -> Vec<Canvas> {
+    let mut levels = vec![canvas];
+    loop {
+        let last = &levels[levels.len() - 1];
+        if last.width <= 1 && last.height <= 1 || last.pixels.is_empty() {
+            break;
+        }
+        let (width, height) = ((last.width / 2).max(1), (last.height / 2).max(1));
+        let mut next = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = Color::BLACK;
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (x * 2 + dx).min(last.width - 1);
+                    let sy = (y * 2 + dy).min(last.height - 1);
+                    sum = sum + last.pixel_at(sx, sy);
+                }
+                next.write(x, y, sum * 0.25);
+            }
+        }
+        levels.push(next);
+    }
+    levels
+}
+
+impl UvPattern for ImageTexture {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        match self.filter {
+            Filter::Nearest => self.nearest(u, v),
+            _ => self.bilinear(0, u, v),
+        }
+    }
+
+    fn uv_pattern_filtered(&self, u: f64, v: f64, duv_dx: (f64, f64), duv_dy: (f64, f64)) -> Color {
+        let (width, height) = (self.levels[0].width as f64, self.levels[0].height as f64);
+        // footprint axes measured in texels of the full size image
+        let x_len = (duv_dx.0 * width).hypot(duv_dx.1 * height);
+        let y_len = (duv_dy.0 * width).hypot(duv_dy.1 * height);
+
+        match self.filter {
+            Filter::Nearest | Filter::Bilinear => self.uv_pattern_at(u, v),
+            Filter::Trilinear => self.trilinear(x_len.max(y_len).max(1.0).log2(), u, v),
+            Filter::Anisotropic => {
+                let (major, minor, axis) = if x_len >= y_len {
+                    (x_len, y_len, duv_dx)
+                } else {
+                    (y_len, x_len, duv_dy)
+                };
+                let probes = (major / minor.max(1e-8))
+                    .ceil()
+                    .clamp(1.0, self.max_anisotropy.max(1) as f64);
+                // with too few probes to cover the footprint, each must blur more
+                let lod = (major / probes).max(minor).max(1.0).log2();
+                let n = probes as usize;
+
+                let mut sum = Color::BLACK;
+                for i in 0..n {
+                    let offset = (i as f64 + 0.5) / probes - 0.5;
+                    sum = sum + self.trilinear(lod, u + axis.0 * offset, v + axis.1 * offset);
+                }
+                sum / probes
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pattern::tests::checker_canvas;
+
+    use super::*;
+
+    // 2x2 image: black and white on top, red and green below
+    fn texture() -> ImageTexture {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.write(1, 0, Color::WHITE);
+        canvas.write(0, 1, Color::new(1.0, 0.0, 0.0));
+        canvas.write(1, 1, Color::new(0.0, 1.0, 0.0));
+        ImageTexture::new(canvas)
+    }
+
+    #[test]
+    fn nearest_filter_picks_containing_texel() {
+        let mut texture = texture();
+        texture.filter = Filter::Nearest;
+        assert_eq!(texture.uv_pattern_at(0.1, 0.9), Color::BLACK);
+        assert_eq!(texture.uv_pattern_at(0.9, 0.9), Color::WHITE);
+        assert_eq!(texture.uv_pattern_at(0.1, 0.1), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(texture.uv_pattern_at(0.9, 0.1), Color::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn bilinear_filter_blends_neighbours() {
+        let mut texture = texture();
+        texture.filter = Filter::Bilinear;
+        assert_eq!(texture.uv_pattern_at(0.25, 0.75), Color::BLACK);
+        assert_eq!(texture.uv_pattern_at(0.5, 0.75), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(texture.uv_pattern_at(0.5, 0.5), Color::new(0.5, 0.5, 0.25));
+    }
+
+    #[test]
+    fn repeat_wraps_around_the_edges() {
+        let texture = texture();
+        assert_eq!(texture.uv_pattern_at(0.0, 0.75), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(texture.uv_pattern_at(1.25, 0.75), Color::BLACK);
+    }
+
+    #[test]
+    fn clamp_holds_the_edge_texel() {
+        let mut texture = texture();
+        texture.wrap = Wrap::Clamp;
+        assert_eq!(texture.uv_pattern_at(0.0, 0.75), Color::BLACK);
+        assert_eq!(texture.uv_pattern_at(1.25, 0.75), Color::WHITE);
+        texture.filter = Filter::Nearest;
+        assert_eq!(texture.uv_pattern_at(-3.0, -3.0), Color::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn pyramid_halves_down_to_one_texel() {
+        let texture = ImageTexture::new(checker_canvas(8));
+        let sizes: Vec<(usize, usize)> = texture
+            .levels()
+            .iter()
+            .map(|l| (l.width, l.height))
+            .collect();
+        assert_eq!(sizes, vec![(8, 8), (4, 4), (2, 2), (1, 1)]);
+        assert_eq!(
+            texture.levels()[1].pixel_at(0, 0),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(
+            texture.levels()[3].pixel_at(0, 0),
+            Color::new(0.5, 0.5, 0.5)
+        );
+    }
+
+    #[test]
+    fn pyramid_handles_non_square_images() {
+        let texture = ImageTexture::new(Canvas::new(4, 1));
+        let sizes: Vec<(usize, usize)> = texture
+            .levels()
+            .iter()
+            .map(|l| (l.width, l.height))
+            .collect();
+        assert_eq!(sizes, vec![(4, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn empty_image_has_one_level_and_samples_black() {
+        let texture = ImageTexture::new(Canvas::new(0, 0));
+        assert_eq!(texture.levels().len(), 1);
+        assert_eq!(texture.uv_pattern_at(0.5, 0.5), Color::BLACK);
+        let c = texture.uv_pattern_filtered(0.5, 0.5, (0.5, 0.0), (0.0, 0.5));
+        assert_eq!(c, Color::BLACK);
+    }
+
+    #[test]
+    fn small_footprint_reads_full_resolution() {
+        let texture = ImageTexture::new(checker_canvas(8));
+        let tiny = (0.001, 0.0);
+        assert_eq!(
+            texture.uv_pattern_filtered(0.0625, 0.9375, tiny, (0.0, 0.001)),
+            texture.uv_pattern_at(0.0625, 0.9375)
+        );
+    }
+
+    #[test]
+    fn wide_footprint_averages_the_image() {
+        let texture = ImageTexture::new(checker_canvas(8));
+        let c = texture.uv_pattern_filtered(0.0625, 0.9375, (1.0, 0.0), (0.0, 1.0));
+        assert_eq!(c, Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn trilinear_blurs_a_stretched_footprint_along_both_axes() {
+        let texture = ImageTexture::new(checker_canvas(8));
+        // four texels across in u, one in v: trilinear picks level 2
+        let c = texture.uv_pattern_filtered(0.0625, 0.9375, (0.5, 0.0), (0.0, 0.125));
+        assert_eq!(c, Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn anisotropic_probes_along_the_long_axis() {
+        let mut canvas = Canvas::new(8, 8);
+        // vertical stripes: every other column white
+        for y in 0..8 {
+            for x in (0..8).step_by(2) {
+                canvas.write(x, y, Color::WHITE);
+            }
+        }
+        let mut texture = ImageTexture::new(canvas);
+        texture.filter = Filter::Anisotropic;
+        // stretched along v, which the stripes do not vary in
+        let c = texture.uv_pattern_filtered(0.0625, 0.5, (0.125 / 8.0, 0.0), (0.0, 0.5));
+        assert_eq!(c, Color::WHITE);
+        texture.filter = Filter::Trilinear;
+        let c = texture.uv_pattern_filtered(0.0625, 0.5, (0.125 / 8.0, 0.0), (0.0, 0.5));
+        assert_eq!(c, Color::new(0.5, 0.5, 0.5));
+    }
+}
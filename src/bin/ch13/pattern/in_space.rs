@@ -0,0 +1,60 @@
+use crate::{
+    matrix::Matrix,
+    shape::Shape,
+    tuple::{Color, Point},
+};
+
+use super::{Footprint, Pattern, PatternSpace};
+
+/// Evaluates a pattern in world, object or UV space instead of object space.
+///
+/// The space is passed on to shape-aware patterns such as `TextureMap` and
+/// `Triplanar`, which keep their own UVs, normals and filtering.
+///
+/// Only the outermost pattern on a material is placed this way: nested
+/// sub-patterns are handed their parent's point, so wrapping one of them
+/// has no effect.
+#[derive(Debug)]
+pub struct InSpace {
+    pattern: Box<dyn Pattern>,
+    space: PatternSpace,
+}
+
+impl InSpace {
+    pub fn new(space: PatternSpace, pattern: Box<dyn Pattern>) -> Self {
+        Self { pattern, space }
+    }
+}
+
+impl Pattern for InSpace {
+    fn pattern_at(&self, point: &Point) -> Color {
+        self.pattern.pattern_at(point)
+    }
+
+    fn pattern_in_space(&self, _space: PatternSpace, shape: &dyn Shape, point: &Point) -> Color {
+        self.pattern.pattern_in_space(self.space, shape, point)
+    }
+
+    fn footprint_in_space(
+        &self,
+        _space: PatternSpace,
+        shape: &dyn Shape,
+        point: &Point,
+        footprint: &Footprint,
+    ) -> Color {
+        self.pattern
+            .footprint_in_space(self.space, shape, point, footprint)
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.pattern.transform()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.pattern.set_transform(transform);
+    }
+
+    fn space(&self) -> PatternSpace {
+        self.space
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{
+    matrix::Matrix,
+    noise::Perlin,
+    tuple::{Color, Point},
+};
+
+use super::{ColorRamp, Pattern};
+
+/// Veins from sine bands along x, distorted by turbulence.
+#[derive(Debug)]
+pub struct Marble {
+    pub ramp: ColorRamp,
+    /// Bands per unit along x.
+    pub frequency: f64,
+    /// Phase shift, in radians, at full turbulence.
+    pub turbulence: f64,
+    pub octaves: u32,
+    pub perlin: Perlin,
+    pub transform: Matrix<4>,
+}
+
+impl Marble {
+    pub fn new(a: Color, b: Color) -> Self {
+        Self {
+            ramp: ColorRamp::between(a, b),
+            frequency: 1.0,
+            turbulence: 5.0,
+            octaves: 6,
+            perlin: Perlin::default(),
+            transform: Default::default(),
+        }
+    }
+
+    /// The band value at `point` in [0, 1], looked up in `ramp`.
+    pub fn value_at(&self, point: &Point) -> f64 {
+        let phase = point.x * self.frequency * std::f64::consts::PI
+            + self.turbulence * self.perlin.turbulence(point, self.octaves, 2.0, 0.5);
+        (phase.sin() + 1.0) / 2.0
+    }
+}
+
+impl Pattern for Marble {
+    fn pattern_at(&self, point: &Point) -> Color {
+        let point = self.transform.inverse() * *point;
+        let t = self.value_at(&point);
+        self.ramp.color_at(t)
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tuple::Tuple;
+
+    use super::*;
+
+    #[test]
+    fn calm_marble_is_sine_bands() {
+        let mut pattern = Marble::new(Color::BLACK, Color::WHITE);
+        pattern.turbulence = 0.0;
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.0, 0.0, 0.0)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(pattern.pattern_at(&Point::new(0.5, 2.0, 1.0)), Color::WHITE);
+        assert_eq!(
+            pattern.pattern_at(&Point::new(1.5, 0.0, -3.0)),
+            Color::BLACK
+        );
+    }
+
+    #[test]
+    fn marble_stays_between_its_colours() {
+        let pattern = Marble::new(Color::BLACK, Color::WHITE);
+        for i in 0..50 {
+            let t = i as f64 * 0.23;
+            let c = pattern.pattern_at(&Point::new(t, t * 0.5, -t));
+            assert!((0.0..=1.0).contains(&c.red));
+        }
+    }
+}
@@ -1,40 +1,142 @@
+pub mod blend;
 pub mod checkerboard;
+pub mod fractal;
 pub mod gradient;
+pub mod image;
+pub mod in_space;
+pub mod marble;
+pub mod perturbed;
+pub mod ramp;
 pub mod ring;
+pub mod scalar;
+pub mod solid;
 pub mod stripe;
+pub mod texture_map;
+pub mod triplanar;
+pub mod uv;
+pub mod wood;
 use std::{cell::RefCell, fmt::Debug, rc::Rc};
 
 pub use checkerboard::Checkerboard;
 pub use gradient::Gradient;
+pub use image::ImageTexture;
+pub use ramp::ColorRamp;
 pub use ring::Ring;
+pub use scalar::ScalarTexture;
+pub use solid::Solid;
 pub use stripe::Stripe;
+pub use texture_map::UvPattern;
 
 use crate::{
     matrix::Matrix,
     shape::Shape,
     transformations::Transformation,
-    tuple::{Color, Point},
+    tuple::{Color, Point, Tuple, Vector},
 };
+
+/// The coordinates a pattern is evaluated in, before its own transform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PatternSpace {
+    /// Fixed in the scene; the pattern does not move with the shape.
+    World,
+    /// Follows the shape's transform.
+    #[default]
+    Object,
+    /// The shape's texture coordinates, as the point `(u, 0, v)`.
+    Uv,
+}
+
+impl PatternSpace {
+    /// Where the world space `point` on `shape` lies in this space.
+    pub fn point_on(&self, shape: &dyn Shape, point: &Point) -> Point {
+        match self {
+            PatternSpace::World => *point,
+            PatternSpace::Object => shape.world_to_object(point),
+            PatternSpace::Uv => {
+                let (u, v) = shape.local_uv_at(&shape.world_to_object(point));
+                Point::new(u, 0.0, v)
+            }
+        }
+    }
+}
+
+/// How far a surface point moves, in world space, between neighbouring pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Footprint {
+    pub dpdx: Vector,
+    pub dpdy: Vector,
+}
+
 pub trait Pattern: Debug {
     fn pattern_at(&self, point: &Point) -> Color;
-    fn pattern_at_shape(&self, shape: &Shape, point: &Point) -> Color {
-        let point = shape.get_transform().inverse().transpose() * *point;
-        self.pattern_at(&point)
+    fn pattern_at_shape(&self, shape: &dyn Shape, point: &Point) -> Color {
+        self.pattern_in_space(self.space(), shape, point)
+    }
+    /// The pattern at the world space `point` on `shape`, handed over in
+    /// `space`. Shape-aware patterns override this rather than
+    /// `pattern_at_shape`, so `InSpace` can pass its space on to them.
+    fn pattern_in_space(&self, space: PatternSpace, shape: &dyn Shape, point: &Point) -> Color {
+        self.pattern_at(&space.point_on(shape, point))
+    }
+    /// The pattern averaged over the area one pixel covers around `point`.
+    ///
+    /// Only a `TextureMap` filters its texture. Composites such as `Blend`,
+    /// `Checkerboard`, `Triplanar` and `CubeMap` evaluate their parts through
+    /// `pattern_at`, and `ScalarTexture` reads its pattern the same way, so
+    /// any image nested inside them is point sampled.
+    fn pattern_at_footprint(
+        &self,
+        shape: &dyn Shape,
+        point: &Point,
+        footprint: &Footprint,
+    ) -> Color {
+        self.footprint_in_space(self.space(), shape, point, footprint)
+    }
+    /// `pattern_at_footprint` in the given space. Patterns that cannot
+    /// filter themselves take a point sample.
+    fn footprint_in_space(
+        &self,
+        space: PatternSpace,
+        shape: &dyn Shape,
+        point: &Point,
+        _footprint: &Footprint,
+    ) -> Color {
+        self.pattern_in_space(space, shape, point)
     }
     fn transform(&self) -> Matrix<4>;
     fn set_transform(&mut self, transform: Matrix<4>);
+    /// The space `pattern_at_shape` hands points over in. Wrap a pattern in
+    /// `InSpace` to pick another one.
+    fn space(&self) -> PatternSpace {
+        PatternSpace::Object
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use crate::{
+        canvas::Canvas,
         matrix::Matrix,
-        shape::{Shape, Sphere},
+        pattern::in_space::InSpace,
+        shape::{Plane, Shape, Sphere},
         tuple::{Color, Tuple},
     };
 
     use super::*;
 
+    /// A `size` by `size` image of alternating white and black texels.
+    pub fn checker_canvas(size: usize) -> Canvas {
+        let mut canvas = Canvas::new(size, size);
+        for y in 0..size {
+            for x in 0..size {
+                if (x + y) % 2 == 0 {
+                    canvas.write(x, y, Color::WHITE);
+                }
+            }
+        }
+        canvas
+    }
+
     #[derive(Debug)]
     pub struct TestPattern {
         transform: Matrix<4>,
@@ -105,4 +207,36 @@ pub mod tests {
         let c = pattern.pattern_at_shape(&shape, &Point::new(2.5, 3.0, 3.5));
         assert_eq!(c, Color::new(0.75, 0.5, 0.25));
     }
+
+    #[test]
+    fn pattern_follows_translated_object() {
+        let mut shape = Sphere::new(0);
+        shape.set_transform(Matrix::default().translation(1.0, 2.0, 3.0));
+        let pattern = TestPattern::new();
+        let c = pattern.pattern_at_shape(&shape, &Point::new(1.5, 2.0, 3.0));
+        assert_eq!(c, Color::new(0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn default_pattern_space_is_object() {
+        assert_eq!(TestPattern::new().space(), PatternSpace::Object);
+    }
+
+    #[test]
+    fn world_space_pattern_ignores_object_transform() {
+        let mut shape = Sphere::new(0);
+        shape.set_transform(Matrix::default().scaling(2.0, 2.0, 2.0));
+        let pattern = InSpace::new(PatternSpace::World, Box::new(TestPattern::new()));
+        let c = pattern.pattern_at_shape(&shape, &Point::new(2.0, 3.0, 4.0));
+        assert_eq!(c, Color::new(2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn uv_space_pattern_reads_texture_coordinates() {
+        let mut shape = Plane::new(0);
+        shape.set_transform(Matrix::default().translation(0.0, 0.0, 5.0));
+        let pattern = InSpace::new(PatternSpace::Uv, Box::new(TestPattern::new()));
+        let c = pattern.pattern_at_shape(&shape, &Point::new(1.25, 0.0, 5.5));
+        assert_eq!(c, Color::new(0.25, 0.0, 0.5));
+    }
 }
@@ -0,0 +1,94 @@
+use crate::{
+    matrix::Matrix,
+    noise::Perlin,
+    tuple::{Color, Point, Tuple, Vector},
+};
+
+use super::Pattern;
+
+/// Jitters the point handed to an inner pattern with 3D noise.
+#[derive(Debug)]
+pub struct Perturbed {
+    pattern: Box<dyn Pattern>,
+    /// Largest distance a point is moved.
+    pub scale: f64,
+    /// Noise features per unit of pattern space.
+    pub frequency: f64,
+    pub perlin: Perlin,
+    pub transform: Matrix<4>,
+}
+
+impl Perturbed {
+    pub fn new(pattern: Box<dyn Pattern>, scale: f64) -> Self {
+        Self {
+            pattern,
+            scale,
+            frequency: 1.0,
+            perlin: Perlin::default(),
+            transform: Default::default(),
+        }
+    }
+}
+
+impl Pattern for Perturbed {
+    fn pattern_at(&self, point: &Point) -> Color {
+        let point = self.transform.inverse() * *point;
+        let p = point * self.frequency;
+        // offsets decorrelate the three axes
+        let jitter = Vector::new(
+            self.perlin.noise(&p),
+            self.perlin.noise(&(p + Vector::new(5.2, 1.3, 7.1))),
+            self.perlin.noise(&(p + Vector::new(1.7, 9.2, 3.4))),
+        );
+        self.pattern.pattern_at(&(point + jitter * self.scale))
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pattern::{tests::TestPattern, Stripe};
+
+    use super::*;
+
+    #[test]
+    fn zero_scale_leaves_pattern_alone() {
+        let pattern = Perturbed::new(Box::new(TestPattern::new()), 0.0);
+        let p = Point::new(0.3, 0.6, 0.9);
+        assert_eq!(pattern.pattern_at(&p), Color::new(0.3, 0.6, 0.9));
+    }
+
+    #[test]
+    fn jitter_is_bounded_by_scale() {
+        let pattern = Perturbed::new(Box::new(TestPattern::new()), 0.1);
+        let mut moved = false;
+        for i in 0..50 {
+            let t = i as f64 * 0.31 + 0.05;
+            let c = pattern.pattern_at(&Point::new(t, 0.5, -t));
+            assert!((c.red - t).abs() <= 0.1);
+            assert!((c.green - 0.5).abs() <= 0.1);
+            assert!((c.blue + t).abs() <= 0.1);
+            moved |= (c.red - t).abs() > 0.001;
+        }
+        assert!(moved);
+    }
+
+    #[test]
+    fn perturbed_stripes_wobble() {
+        let stripes = Stripe::new(Color::WHITE, Color::BLACK);
+        let pattern = Perturbed::new(Box::new(stripes), 0.5);
+        // along a line parallel to the stripes the colour changes
+        let colors: Vec<Color> = (0..40)
+            .map(|i| pattern.pattern_at(&Point::new(0.9, 0.0, i as f64 * 0.13)))
+            .collect();
+        assert!(colors.contains(&Color::WHITE));
+        assert!(colors.contains(&Color::BLACK));
+    }
+}
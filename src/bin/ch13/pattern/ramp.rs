@@ -0,0 +1,115 @@
+use crate::tuple::Color;
+
+/// How a `ColorRamp` fills the space between neighbouring stops.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    /// Eases in and out of each stop.
+    Smoothstep,
+    /// Holds each stop's colour until the next one.
+    Constant,
+}
+
+/// Maps a scalar to a colour through any number of stops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp {
+    stops: Vec<(f64, Color)>,
+    pub interpolation: Interpolation,
+}
+
+impl ColorRamp {
+    /// Stops may be given in any order; values outside them take the end colours.
+    pub fn new(mut stops: Vec<(f64, Color)>) -> Self {
+        assert!(!stops.is_empty(), "a colour ramp needs at least one stop");
+        stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        Self {
+            stops,
+            interpolation: Interpolation::Linear,
+        }
+    }
+
+    /// The two stop ramp from `a` at 0 to `b` at 1.
+    pub fn between(a: Color, b: Color) -> Self {
+        Self::new(vec![(0.0, a), (1.0, b)])
+    }
+
+    pub fn stops(&self) -> &[(f64, Color)] {
+        &self.stops
+    }
+
+    pub fn color_at(&self, t: f64) -> Color {
+        let first = self.stops[0];
+        if t <= first.0 {
+            return first.1;
+        }
+        for pair in self.stops.windows(2) {
+            let ((t0, c0), (t1, c1)) = (pair[0], pair[1]);
+            if t < t1 {
+                let f = (t - t0) / (t1 - t0);
+                let f = match self.interpolation {
+                    Interpolation::Linear => f,
+                    Interpolation::Smoothstep => f * f * (3.0 - 2.0 * f),
+                    Interpolation::Constant => 0.0,
+                };
+                return c0 + (c1 - c0) * f;
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops_are_sorted() {
+        let ramp = ColorRamp::new(vec![
+            (1.0, Color::new(0.0, 0.0, 1.0)),
+            (0.0, Color::BLACK),
+            (0.5, Color::new(1.0, 0.0, 0.0)),
+        ]);
+        let positions: Vec<f64> = ramp.stops().iter().map(|s| s.0).collect();
+        assert_eq!(positions, vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn linear_ramp_interpolates_between_neighbours() {
+        let ramp = ColorRamp::new(vec![
+            (1.0, Color::new(0.0, 0.0, 1.0)),
+            (0.0, Color::BLACK),
+            (0.5, Color::new(1.0, 0.0, 0.0)),
+        ]);
+        assert_eq!(ramp.color_at(0.25), Color::new(0.5, 0.0, 0.0));
+        assert_eq!(ramp.color_at(0.5), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(ramp.color_at(0.75), Color::new(0.5, 0.0, 0.5));
+    }
+
+    #[test]
+    fn ramp_clamps_outside_its_stops() {
+        let ramp = ColorRamp::new(vec![(0.0, Color::BLACK), (1.0, Color::new(0.0, 0.0, 1.0))]);
+        assert_eq!(ramp.color_at(-2.0), Color::BLACK);
+        assert_eq!(ramp.color_at(3.0), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn smoothstep_ramp_eases_near_stops() {
+        let mut ramp = ColorRamp::new(vec![(0.0, Color::BLACK), (0.5, Color::new(1.0, 0.0, 0.0))]);
+        ramp.interpolation = Interpolation::Smoothstep;
+        assert_eq!(ramp.color_at(0.25), Color::new(0.5, 0.0, 0.0));
+        assert_eq!(ramp.color_at(0.05), Color::new(0.028, 0.0, 0.0));
+    }
+
+    #[test]
+    fn constant_ramp_holds_each_stop() {
+        let mut ramp = ColorRamp::new(vec![
+            (1.0, Color::new(0.0, 0.0, 1.0)),
+            (0.0, Color::BLACK),
+            (0.5, Color::new(1.0, 0.0, 0.0)),
+        ]);
+        ramp.interpolation = Interpolation::Constant;
+        assert_eq!(ramp.color_at(0.49), Color::BLACK);
+        assert_eq!(ramp.color_at(0.5), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(ramp.color_at(0.99), Color::new(1.0, 0.0, 0.0));
+    }
+}
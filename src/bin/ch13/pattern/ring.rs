@@ -3,16 +3,20 @@ use crate::{
     tuple::{Color, Point},
 };
 
-use super::Pattern;
+use super::{Pattern, Solid};
 
 #[derive(Debug)]
 pub struct Ring {
-    a: Color,
-    b: Color,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
     pub transform: Matrix<4>,
 }
 impl Ring {
     pub fn new(a: Color, b: Color) -> Self {
+        Self::nested(Box::new(Solid::new(a)), Box::new(Solid::new(b)))
+    }
+
+    pub fn nested(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
         Self {
             a,
             b,
@@ -23,19 +27,19 @@ impl Ring {
 
 impl Pattern for Ring {
     fn transform(&self) -> Matrix<4> {
-        todo!()
+        self.transform
     }
 
     fn set_transform(&mut self, transform: Matrix<4>) {
-        todo!()
+        self.transform = transform;
     }
 
     fn pattern_at(&self, point: &Point) -> Color {
         let point = self.transform.inverse() * *point;
         if (point.x.powi(2) + point.z.powi(2)).sqrt().floor() % 2.0 == 0.0 {
-            self.a
+            self.a.pattern_at(&point)
         } else {
-            self.b
+            self.b.pattern_at(&point)
         }
     }
 }
@@ -0,0 +1,109 @@
+use crate::{
+    shape::Shape,
+    tuple::{Color, Point},
+};
+
+use super::Pattern;
+
+/// Which part of a pattern's colour a `ScalarTexture` reads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    /// Rec. 709 weighted brightness.
+    Luminance,
+}
+
+impl Channel {
+    pub fn of(&self, color: Color) -> f64 {
+        match self {
+            Channel::Red => color.red,
+            Channel::Green => color.green,
+            Channel::Blue => color.blue,
+            Channel::Luminance => 0.2126 * color.red + 0.7152 * color.green + 0.0722 * color.blue,
+        }
+    }
+}
+
+/// A single number per point, read from a pattern and remapped to `low..high`.
+#[derive(Debug)]
+pub struct ScalarTexture {
+    pattern: Box<dyn Pattern>,
+    pub channel: Channel,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl ScalarTexture {
+    pub fn new(pattern: Box<dyn Pattern>, channel: Channel) -> Self {
+        Self {
+            pattern,
+            channel,
+            low: 0.0,
+            high: 1.0,
+        }
+    }
+
+    pub fn with_range(mut self, low: f64, high: f64) -> Self {
+        self.low = low;
+        self.high = high;
+        self
+    }
+
+    pub fn value_at_shape(&self, shape: &dyn Shape, point: &Point) -> f64 {
+        let v = self.channel.of(self.pattern.pattern_at_shape(shape, point));
+        self.low + (self.high - self.low) * v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pattern::{Checkerboard, Solid},
+        shape::Sphere,
+        tuple::Tuple,
+    };
+
+    use super::*;
+
+    #[test]
+    fn channels_pick_from_colour() {
+        let c = Color::new(0.2, 0.5, 0.8);
+        assert_eq!(Channel::Red.of(c), 0.2);
+        assert_eq!(Channel::Green.of(c), 0.5);
+        assert_eq!(Channel::Blue.of(c), 0.8);
+        assert_eq!(Channel::Luminance.of(Color::WHITE), 1.0);
+    }
+
+    #[test]
+    fn scalar_texture_remaps_to_range() {
+        let shape = Sphere::new(0);
+        let texture = ScalarTexture::new(
+            Box::new(Solid::new(Color::new(0.5, 0.0, 0.0))),
+            Channel::Red,
+        )
+        .with_range(10.0, 20.0);
+        assert_eq!(
+            texture.value_at_shape(&shape, &Point::new(0.0, 0.0, 0.0)),
+            15.0
+        );
+    }
+
+    #[test]
+    fn scalar_texture_follows_pattern() {
+        let shape = Sphere::new(0);
+        let texture = ScalarTexture::new(
+            Box::new(Checkerboard::new(Color::WHITE, Color::BLACK)),
+            Channel::Luminance,
+        );
+        assert_eq!(
+            texture.value_at_shape(&shape, &Point::new(0.5, 0.0, 0.0)),
+            1.0
+        );
+        assert_eq!(
+            texture.value_at_shape(&shape, &Point::new(1.5, 0.0, 0.0)),
+            0.0
+        );
+    }
+}
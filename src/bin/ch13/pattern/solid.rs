@@ -0,0 +1,56 @@
+use crate::{
+    matrix::Matrix,
+    tuple::{Color, Point},
+};
+
+use super::Pattern;
+
+/// The same colour everywhere; the leaf of a nested pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Solid {
+    color: Color,
+    pub transform: Matrix<4>,
+}
+
+impl Solid {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            transform: Default::default(),
+        }
+    }
+}
+
+impl Pattern for Solid {
+    fn pattern_at(&self, _point: &Point) -> Color {
+        self.color
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tuple::Tuple;
+
+    use super::*;
+
+    #[test]
+    fn solid_pattern_is_constant() {
+        let pattern = Solid::new(Color::new(0.2, 0.4, 0.6));
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.0, 0.0, 0.0)),
+            Color::new(0.2, 0.4, 0.6)
+        );
+        assert_eq!(
+            pattern.pattern_at(&Point::new(-3.5, 7.0, 1.2)),
+            Color::new(0.2, 0.4, 0.6)
+        );
+    }
+}
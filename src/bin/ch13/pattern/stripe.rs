@@ -8,17 +8,22 @@ use crate::{
     tuple::{Color, Point},
 };
 
-use super::Pattern;
+use super::{Pattern, Solid};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug)]
 pub struct Stripe {
-    a: Color,
-    b: Color,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
     pub transform: Matrix<4>,
 }
 
 impl Stripe {
     pub fn new(a: Color, b: Color) -> Self {
+        Self::nested(Box::new(Solid::new(a)), Box::new(Solid::new(b)))
+    }
+
+    /// Alternating bands of two sub-patterns.
+    pub fn nested(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
         Self {
             a,
             b,
@@ -39,9 +44,9 @@ impl Pattern for Stripe {
     fn pattern_at(&self, point: &Point) -> Color {
         let point = self.transform.inverse() * *point;
         if approx_eq!(f64, point.x.floor() % 2.0, 0.0) {
-            self.a
+            self.a.pattern_at(&point)
         } else {
-            self.b
+            self.b.pattern_at(&point)
         }
     }
 }
@@ -62,8 +67,9 @@ mod tests {
     #[test]
     fn create_stripe_pattern() {
         let pattern = Stripe::new(Color::WHITE, Color::BLACK);
-        assert_eq!(pattern.a, Color::WHITE);
-        assert_eq!(pattern.b, Color::BLACK);
+        let origin = Point::new(0.0, 0.0, 0.0);
+        assert_eq!(pattern.a.pattern_at(&origin), Color::WHITE);
+        assert_eq!(pattern.b.pattern_at(&origin), Color::BLACK);
     }
 
     #[test]
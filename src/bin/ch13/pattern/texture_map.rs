@@ -0,0 +1,455 @@
+use std::fmt::Debug;
+
+use crate::{
+    matrix::Matrix,
+    shape::Shape,
+    tuple::{Color, Point, Vector},
+};
+
+use super::{
+    uv::{cube_uv, face_from_point, UvMapping},
+    Footprint, Pattern, PatternSpace,
+};
+
+/// A two dimensional pattern evaluated in (u, v) texture space.
+pub trait UvPattern: Debug {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color;
+
+    /// Averages the pattern over the area a pixel covers, given how far (u, v)
+    /// moves per pixel step in x and in y. Point samples by default.
+    fn uv_pattern_filtered(
+        &self,
+        u: f64,
+        v: f64,
+        _duv_dx: (f64, f64),
+        _duv_dy: (f64, f64),
+    ) -> Color {
+        self.uv_pattern_at(u, v)
+    }
+}
+
+#[derive(Debug)]
+pub struct UvCheckers {
+    width: f64,
+    height: f64,
+    a: Color,
+    b: Color,
+}
+
+impl UvCheckers {
+    pub fn new(width: f64, height: f64, a: Color, b: Color) -> Self {
+        Self {
+            width,
+            height,
+            a,
+            b,
+        }
+    }
+}
+
+impl UvPattern for UvCheckers {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let u2 = (u * self.width).floor();
+        let v2 = (v * self.height).floor();
+        if (u2 + v2).rem_euclid(2.0) == 0.0 {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+/// Marks the corners of the unit square, handy for checking orientation.
+#[derive(Debug)]
+pub struct UvAlignCheck {
+    main: Color,
+    ul: Color,
+    ur: Color,
+    bl: Color,
+    br: Color,
+}
+
+impl UvAlignCheck {
+    pub fn new(main: Color, ul: Color, ur: Color, bl: Color, br: Color) -> Self {
+        Self {
+            main,
+            ul,
+            ur,
+            bl,
+            br,
+        }
+    }
+}
+
+impl UvPattern for UvAlignCheck {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        if v > 0.8 {
+            if u < 0.2 {
+                return self.ul;
+            }
+            if u > 0.8 {
+                return self.ur;
+            }
+        } else if v < 0.2 {
+            if u < 0.2 {
+                return self.bl;
+            }
+            if u > 0.8 {
+                return self.br;
+            }
+        }
+
+        self.main
+    }
+}
+
+/// Wraps a `UvPattern` around a surface with the chosen mapping.
+#[derive(Debug)]
+pub struct TextureMap {
+    pattern: Box<dyn UvPattern>,
+    mapping: UvMapping,
+    pub transform: Matrix<4>,
+}
+
+impl TextureMap {
+    pub fn new(pattern: Box<dyn UvPattern>, mapping: UvMapping) -> Self {
+        Self {
+            pattern,
+            mapping,
+            transform: Default::default(),
+        }
+    }
+
+    /// Texture coordinates of the world space `point` on `shape`.
+    fn uv_on(&self, space: PatternSpace, shape: &dyn Shape, point: &Point) -> (f64, f64) {
+        if self.mapping == UvMapping::Shape {
+            shape.local_uv_at(&shape.world_to_object(point))
+        } else {
+            let point = self.transform.inverse() * space.point_on(shape, point);
+            self.mapping.map(&point)
+        }
+    }
+}
+
+/// `b - a` for coordinates that wrap at 1, taking the short way round.
+fn wrapped_difference(b: f64, a: f64) -> f64 {
+    (b - a + 0.5).rem_euclid(1.0) - 0.5
+}
+
+impl Pattern for TextureMap {
+    fn pattern_at(&self, point: &Point) -> Color {
+        let point = self.transform.inverse() * *point;
+        let (u, v) = self.mapping.map(&point);
+        self.pattern.uv_pattern_at(u, v)
+    }
+
+    /// `UvMapping::Shape` asks the shape for its own coordinates, so the
+    /// pattern transform is not applied in that case.
+    fn pattern_in_space(&self, space: PatternSpace, shape: &dyn Shape, point: &Point) -> Color {
+        let (u, v) = self.uv_on(space, shape, point);
+        self.pattern.uv_pattern_at(u, v)
+    }
+
+    /// Maps the points a pixel step away as well, so the texture can filter
+    /// over the difference in (u, v).
+    fn footprint_in_space(
+        &self,
+        space: PatternSpace,
+        shape: &dyn Shape,
+        point: &Point,
+        footprint: &Footprint,
+    ) -> Color {
+        let (u, v) = self.uv_on(space, shape, point);
+        let delta = |offset: Vector| {
+            let (u1, v1) = self.uv_on(space, shape, &(*point + offset));
+            (wrapped_difference(u1, u), wrapped_difference(v1, v))
+        };
+        self.pattern
+            .uv_pattern_filtered(u, v, delta(footprint.dpdx), delta(footprint.dpdy))
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = transform;
+    }
+}
+
+/// A separate `UvPattern` for each face of a cube, indexed by `CubeFace`.
+#[derive(Debug)]
+pub struct CubeMap {
+    faces: [Box<dyn UvPattern>; 6],
+    pub transform: Matrix<4>,
+}
+
+impl CubeMap {
+    /// Faces are given in `CubeFace` order: left, right, front, back, up, down.
+    pub fn new(faces: [Box<dyn UvPattern>; 6]) -> Self {
+        Self {
+            faces,
+            transform: Default::default(),
+        }
+    }
+}
+
+impl Pattern for CubeMap {
+    fn pattern_at(&self, point: &Point) -> Color {
+        let point = self.transform.inverse() * *point;
+        let face = face_from_point(&point);
+        let (u, v) = cube_uv(face, &point);
+        self.faces[face.index()].uv_pattern_at(u, v)
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pattern::{in_space::InSpace, tests::checker_canvas, ImageTexture},
+        shape::{Cube, Cylinder, Plane},
+        transformations::Transformation,
+        tuple::Tuple,
+    };
+
+    use super::*;
+
+    const RED: Color = Color {
+        red: 1.0,
+        green: 0.0,
+        blue: 0.0,
+    };
+    const YELLOW: Color = Color {
+        red: 1.0,
+        green: 1.0,
+        blue: 0.0,
+    };
+    const GREEN: Color = Color {
+        red: 0.0,
+        green: 1.0,
+        blue: 0.0,
+    };
+    const CYAN: Color = Color {
+        red: 0.0,
+        green: 1.0,
+        blue: 1.0,
+    };
+
+    #[test]
+    fn checker_pattern_in_2d() {
+        let checkers = UvCheckers::new(2.0, 2.0, Color::BLACK, Color::WHITE);
+        assert_eq!(checkers.uv_pattern_at(0.0, 0.0), Color::BLACK);
+        assert_eq!(checkers.uv_pattern_at(0.5, 0.0), Color::WHITE);
+        assert_eq!(checkers.uv_pattern_at(0.0, 0.5), Color::WHITE);
+        assert_eq!(checkers.uv_pattern_at(0.5, 0.5), Color::BLACK);
+        assert_eq!(checkers.uv_pattern_at(1.0, 1.0), Color::BLACK);
+    }
+
+    #[test]
+    fn texture_map_with_spherical_map() {
+        let checkers = UvCheckers::new(16.0, 8.0, Color::BLACK, Color::WHITE);
+        let pattern = TextureMap::new(Box::new(checkers), UvMapping::Spherical);
+        let examples = vec![
+            (Point::new(0.4315, 0.4670, 0.7719), Color::WHITE),
+            (Point::new(-0.9654, 0.2552, -0.0534), Color::BLACK),
+            (Point::new(0.1039, 0.7090, 0.6975), Color::WHITE),
+            (Point::new(-0.4986, -0.7856, -0.3663), Color::BLACK),
+            (Point::new(-0.0317, -0.9395, 0.3411), Color::BLACK),
+            (Point::new(0.4809, -0.7721, 0.4154), Color::BLACK),
+            (Point::new(0.0285, -0.9612, -0.2745), Color::BLACK),
+            (Point::new(-0.5734, -0.2162, -0.7903), Color::WHITE),
+            (Point::new(0.7688, -0.1470, 0.6223), Color::BLACK),
+            (Point::new(-0.7652, 0.2175, 0.6060), Color::BLACK),
+        ];
+        for (point, color) in examples {
+            assert_eq!(pattern.pattern_at(&point), color);
+        }
+    }
+
+    #[test]
+    fn texture_map_with_cylindrical_and_cubic_maps() {
+        let align = || Box::new(UvAlignCheck::new(Color::WHITE, RED, YELLOW, GREEN, CYAN));
+        let pattern = TextureMap::new(align(), UvMapping::Cylindrical);
+        assert_eq!(pattern.pattern_at(&Point::new(0.0, 0.9, -1.0)), RED);
+        assert_eq!(pattern.pattern_at(&Point::new(0.0, 0.5, 1.0)), Color::WHITE);
+        let pattern = TextureMap::new(align(), UvMapping::Cubic);
+        assert_eq!(pattern.pattern_at(&Point::new(-0.9, 0.9, 1.0)), RED);
+        assert_eq!(pattern.pattern_at(&Point::new(0.9, -0.9, 1.0)), CYAN);
+    }
+
+    #[test]
+    fn layout_of_align_check_pattern() {
+        let pattern = UvAlignCheck::new(Color::WHITE, RED, YELLOW, GREEN, CYAN);
+        assert_eq!(pattern.uv_pattern_at(0.5, 0.5), Color::WHITE);
+        assert_eq!(pattern.uv_pattern_at(0.1, 0.9), RED);
+        assert_eq!(pattern.uv_pattern_at(0.9, 0.9), YELLOW);
+        assert_eq!(pattern.uv_pattern_at(0.1, 0.1), GREEN);
+        assert_eq!(pattern.uv_pattern_at(0.9, 0.1), CYAN);
+    }
+
+    #[test]
+    fn cube_map_picks_pattern_by_face() {
+        let face = |c: f64| -> Box<dyn UvPattern> {
+            Box::new(UvCheckers::new(1.0, 1.0, Color::new(c, c, c), Color::BLACK))
+        };
+        let pattern = CubeMap::new([
+            face(0.1),
+            face(0.2),
+            face(0.3),
+            face(0.4),
+            face(0.5),
+            face(0.6),
+        ]);
+        let examples = vec![
+            (Point::new(-1.0, 0.0, 0.0), 0.1),
+            (Point::new(1.0, 0.0, 0.0), 0.2),
+            (Point::new(0.0, 0.0, 1.0), 0.3),
+            (Point::new(0.0, 0.0, -1.0), 0.4),
+            (Point::new(0.0, 1.0, 0.0), 0.5),
+            (Point::new(0.0, -1.0, 0.0), 0.6),
+        ];
+        for (point, c) in examples {
+            assert_eq!(pattern.pattern_at(&point), Color::new(c, c, c));
+        }
+    }
+
+    #[test]
+    fn cube_map_corners_line_up() {
+        let align = || -> Box<dyn UvPattern> {
+            Box::new(UvAlignCheck::new(Color::WHITE, RED, YELLOW, GREEN, CYAN))
+        };
+        let pattern = CubeMap::new([align(), align(), align(), align(), align(), align()]);
+        assert_eq!(pattern.pattern_at(&Point::new(-0.9, 0.9, 1.0)), RED);
+        assert_eq!(pattern.pattern_at(&Point::new(0.9, 0.9, 1.0)), YELLOW);
+        assert_eq!(pattern.pattern_at(&Point::new(-0.9, -0.9, 1.0)), GREEN);
+        assert_eq!(pattern.pattern_at(&Point::new(0.9, -0.9, 1.0)), CYAN);
+        assert_eq!(pattern.pattern_at(&Point::new(0.0, 0.0, 1.0)), Color::WHITE);
+    }
+
+    #[test]
+    fn shape_mapping_uses_the_shapes_coordinates() {
+        let pattern = TextureMap::new(
+            Box::new(UvCheckers::new(2.0, 2.0, Color::BLACK, Color::WHITE)),
+            UvMapping::Shape,
+        );
+        let plane = Plane::new(0);
+        assert_eq!(
+            pattern.pattern_at_shape(&plane, &Point::new(0.25, 0.0, 0.25)),
+            Color::BLACK
+        );
+        assert_eq!(
+            pattern.pattern_at_shape(&plane, &Point::new(0.75, 0.0, 0.25)),
+            Color::WHITE
+        );
+
+        let mut cylinder = Cylinder::new(1);
+        cylinder.minimum = 0.0;
+        cylinder.maximum = 1.0;
+        cylinder.closed = true;
+        assert_eq!(
+            pattern.pattern_at_shape(&cylinder, &Point::new(-0.5, 1.0, 0.5)),
+            Color::BLACK
+        );
+        assert_eq!(
+            pattern.pattern_at_shape(&cylinder, &Point::new(0.5, 1.0, 0.5)),
+            Color::WHITE
+        );
+
+        let cube = Cube::new(2);
+        assert_eq!(
+            pattern.pattern_at_shape(&cube, &Point::new(-0.5, -0.5, 1.0)),
+            Color::BLACK
+        );
+        assert_eq!(
+            pattern.pattern_at_shape(&cube, &Point::new(1.0, -0.5, 0.5)),
+            Color::BLACK
+        );
+        assert_eq!(
+            pattern.pattern_at_shape(&cube, &Point::new(0.5, -0.5, 1.0)),
+            Color::WHITE
+        );
+    }
+
+    #[test]
+    fn in_space_keeps_the_shape_mapping() {
+        let texture = TextureMap::new(
+            Box::new(UvCheckers::new(2.0, 2.0, Color::BLACK, Color::WHITE)),
+            UvMapping::Shape,
+        );
+        let pattern = InSpace::new(PatternSpace::World, Box::new(texture));
+        let mut plane = Plane::new(0);
+        plane.transform = Matrix::default().translation(0.0, 0.0, 5.0);
+        let footprint = Footprint {
+            dpdx: Vector::new(0.001, 0.0, 0.0),
+            dpdy: Vector::new(0.0, 0.0, 0.001),
+        };
+        let examples = vec![
+            (Point::new(0.25, 0.0, 5.25), Color::BLACK),
+            (Point::new(0.75, 0.0, 5.25), Color::WHITE),
+            (Point::new(0.75, 0.0, 5.75), Color::BLACK),
+        ];
+        for (point, color) in examples {
+            assert_eq!(pattern.pattern_at_shape(&plane, &point), color);
+            assert_eq!(
+                pattern.pattern_at_footprint(&plane, &point, &footprint),
+                color
+            );
+        }
+    }
+
+    #[test]
+    fn shape_mapping_without_a_shape_falls_back_to_spherical() {
+        let checkers = || Box::new(UvCheckers::new(16.0, 8.0, Color::BLACK, Color::WHITE));
+        let shape_mapped = TextureMap::new(checkers(), UvMapping::Shape);
+        let spherical = TextureMap::new(checkers(), UvMapping::Spherical);
+        for point in [
+            Point::new(0.4315, 0.4670, 0.7719),
+            Point::new(-0.9654, 0.2552, -0.0534),
+            Point::new(0.0, 0.0, 0.0),
+        ] {
+            assert_eq!(
+                shape_mapped.pattern_at(&point),
+                spherical.pattern_at(&point)
+            );
+        }
+    }
+
+    #[test]
+    fn footprint_filters_image_over_covered_area() {
+        let canvas = checker_canvas(8);
+        let pattern = TextureMap::new(Box::new(ImageTexture::new(canvas)), UvMapping::Planar);
+        let plane = Plane::new(0);
+        let p = Point::new(0.0625, 0.0, 0.0625);
+        // half the image per pixel
+        let wide = Footprint {
+            dpdx: Vector::new(0.5, 0.0, 0.0),
+            dpdy: Vector::new(0.0, 0.0, 0.5),
+        };
+        assert_eq!(
+            pattern.pattern_at_footprint(&plane, &p, &wide),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        let narrow = Footprint {
+            dpdx: Vector::new(0.001, 0.0, 0.0),
+            dpdy: Vector::new(0.0, 0.0, 0.001),
+        };
+        assert_eq!(
+            pattern.pattern_at_footprint(&plane, &p, &narrow),
+            pattern.pattern_at_shape(&plane, &p)
+        );
+    }
+
+    #[test]
+    fn footprint_deltas_wrap_across_the_seam() {
+        assert!((wrapped_difference(0.02, 0.98) - 0.04).abs() < 1e-9);
+        assert!((wrapped_difference(0.98, 0.02) + 0.04).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    matrix::Matrix,
+    shape::Shape,
+    tuple::{Color, Point, Tuple, Vector},
+};
+
+use super::{texture_map::UvPattern, Pattern, PatternSpace};
+
+/// Projects a 2D texture along the x, y and z axes and blends the three
+/// projections by how squarely the surface faces each axis.
+///
+/// Needs no UV coordinates, so it works on any shape.
+#[derive(Debug)]
+pub struct Triplanar {
+    texture: Box<dyn UvPattern>,
+    /// Higher values narrow the seams where projections blend.
+    pub sharpness: f64,
+    pub transform: Matrix<4>,
+}
+
+impl Triplanar {
+    pub fn new(texture: Box<dyn UvPattern>) -> Self {
+        Self {
+            texture,
+            sharpness: 4.0,
+            transform: Default::default(),
+        }
+    }
+
+    /// Blends the projections at `point` for a surface with normal `normal`,
+    /// both in pattern space.
+    fn blend(&self, point: &Point, normal: &Vector) -> Color {
+        let wx = normal.x.abs().powf(self.sharpness);
+        let wy = normal.y.abs().powf(self.sharpness);
+        let wz = normal.z.abs().powf(self.sharpness);
+        let total = wx + wy + wz;
+        if total == 0.0 {
+            return self.texture.uv_pattern_at(0.0, 0.0);
+        }
+
+        let uv = |a: f64, b: f64| (a.rem_euclid(1.0), b.rem_euclid(1.0));
+        let mut color = Color::BLACK;
+        for (w, (u, v)) in [
+            (wx, uv(point.z, point.y)),
+            (wy, uv(point.x, point.z)),
+            (wz, uv(point.x, point.y)),
+        ] {
+            if w > 0.0 {
+                color = color + self.texture.uv_pattern_at(u, v) * (w / total);
+            }
+        }
+        color
+    }
+}
+
+impl Pattern for Triplanar {
+    /// Without a shape the normal is taken to point away from the origin,
+    /// which is exact for spheres.
+    fn pattern_at(&self, point: &Point) -> Color {
+        let point = self.transform.inverse() * *point;
+        let normal = Vector::new(point.x, point.y, point.z);
+        self.blend(&point, &normal)
+    }
+
+    /// UV space has no normal to blend by, so it is treated as object space.
+    fn pattern_in_space(&self, space: PatternSpace, shape: &dyn Shape, point: &Point) -> Color {
+        let (object_point, normal) = match space {
+            PatternSpace::World => (*point, shape.normal_at(*point)),
+            PatternSpace::Object | PatternSpace::Uv => {
+                let object_point = shape.world_to_object(point);
+                (object_point, shape.local_normal_at(&object_point))
+            }
+        };
+        // normals go into pattern space by the inverse transpose of the point map
+        let normal = (self.transform.transpose() * normal).normalize();
+        let pattern_point = self.transform.inverse() * object_point;
+        self.blend(&pattern_point, &normal)
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pattern::texture_map::UvCheckers,
+        shape::{Cube, Sphere},
+        transformations::Transformation,
+    };
+
+    use super::*;
+
+    #[test]
+    fn each_cube_face_uses_its_own_projection() {
+        let pattern = Triplanar::new(Box::new(UvCheckers::new(
+            2.0,
+            2.0,
+            Color::BLACK,
+            Color::WHITE,
+        )));
+        let cube = Cube::new(0);
+        // on the +x face u comes from z and v from y
+        assert_eq!(
+            pattern.pattern_at_shape(&cube, &Point::new(1.0, 0.25, 0.75)),
+            Color::WHITE
+        );
+        assert_eq!(
+            pattern.pattern_at_shape(&cube, &Point::new(1.0, 0.75, 0.75)),
+            Color::BLACK
+        );
+        // on the top face u comes from x and v from z
+        assert_eq!(
+            pattern.pattern_at_shape(&cube, &Point::new(0.75, 1.0, 0.25)),
+            Color::WHITE
+        );
+        // on the front face u comes from x and v from y
+        assert_eq!(
+            pattern.pattern_at_shape(&cube, &Point::new(0.25, 0.25, -1.0)),
+            Color::BLACK
+        );
+    }
+
+    #[test]
+    fn projections_blend_on_diagonals() {
+        let mut pattern = Triplanar::new(Box::new(UvCheckers::new(
+            2.0,
+            2.0,
+            Color::BLACK,
+            Color::WHITE,
+        )));
+        pattern.sharpness = 1.0;
+        let sphere = Sphere::new(0);
+        let d = std::f64::consts::FRAC_1_SQRT_2;
+        // x projection reads (0.7071, 0.0) -> white, z reads (0.7071, 0.0) -> white
+        let c = pattern.pattern_at_shape(&sphere, &Point::new(d, 0.0, d));
+        assert_eq!(c, Color::WHITE);
+        // x projection reads (-0.7071, 0.0) -> black, z reads (0.7071, 0.0) -> white
+        let c = pattern.pattern_at_shape(&sphere, &Point::new(d, 0.0, -d));
+        assert_eq!(c, Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn triplanar_respects_shape_transform() {
+        let pattern = Triplanar::new(Box::new(UvCheckers::new(
+            2.0,
+            2.0,
+            Color::BLACK,
+            Color::WHITE,
+        )));
+        let mut cube = Cube::new(0);
+        cube.transform = Matrix::default().scaling(2.0, 2.0, 2.0);
+        assert_eq!(
+            pattern.pattern_at_shape(&cube, &Point::new(2.0, 0.5, 1.5)),
+            Color::WHITE
+        );
+        assert_eq!(
+            pattern.pattern_at_shape(&cube, &Point::new(2.0, 1.5, 1.5)),
+            Color::BLACK
+        );
+    }
+}
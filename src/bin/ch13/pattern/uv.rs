@@ -0,0 +1,244 @@
+use std::f64::consts::PI;
+
+use crate::tuple::{Point, Tuple, Vector};
+
+/// Ways of flattening a point in pattern space into (u, v) texture coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UvMapping {
+    Spherical,
+    Planar,
+    Cylindrical,
+    /// Each face of the unit cube gets its own copy of the unit square.
+    Cubic,
+    /// Defer to the shape's own `local_uv_at`.
+    ///
+    /// Only a `TextureMap` evaluated against its shape can do that. Through
+    /// `pattern_at`, e.g. nested inside `Blend` or `Perturbed`, it falls back
+    /// to `Spherical`.
+    Shape,
+}
+
+impl UvMapping {
+    pub fn map(&self, p: &Point) -> (f64, f64) {
+        match self {
+            UvMapping::Spherical => spherical_map(p),
+            // no shape to ask here
+            UvMapping::Shape => spherical_map(p),
+            UvMapping::Planar => planar_map(p),
+            UvMapping::Cylindrical => cylindrical_map(p),
+            UvMapping::Cubic => cube_map(p),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeFace {
+    Left,
+    Right,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+impl CubeFace {
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// The centre has no direction, so it maps to the middle of the texture.
+pub fn spherical_map(p: &Point) -> (f64, f64) {
+    let theta = p.x.atan2(p.z);
+    let radius = Vector::new(p.x, p.y, p.z).magnitude();
+    if radius == 0.0 {
+        return (0.5, 0.5);
+    }
+    let phi = (p.y / radius).acos();
+    let raw_u = theta / (2.0 * PI);
+    let u = 1.0 - (raw_u + 0.5);
+    let v = 1.0 - phi / PI;
+
+    (u, v)
+}
+
+pub fn planar_map(p: &Point) -> (f64, f64) {
+    (p.x.rem_euclid(1.0), p.z.rem_euclid(1.0))
+}
+
+pub fn cylindrical_map(p: &Point) -> (f64, f64) {
+    let theta = p.x.atan2(p.z);
+    let raw_u = theta / (2.0 * PI);
+    let u = 1.0 - (raw_u + 0.5);
+    let v = p.y.rem_euclid(1.0);
+
+    (u, v)
+}
+
+/// Polar coordinates on a disc of the given radius, for the caps of round shapes.
+pub fn disc_map(p: &Point, radius: f64) -> (f64, f64) {
+    if radius <= 0.0 {
+        return (0.5, 0.5);
+    }
+    ((p.x / radius + 1.0) / 2.0, (1.0 - p.z / radius) / 2.0)
+}
+
+pub fn face_from_point(p: &Point) -> CubeFace {
+    let coord = p.x.abs().max(p.y.abs()).max(p.z.abs());
+
+    if coord == p.x {
+        CubeFace::Right
+    } else if coord == -p.x {
+        CubeFace::Left
+    } else if coord == p.y {
+        CubeFace::Up
+    } else if coord == -p.y {
+        CubeFace::Down
+    } else if coord == p.z {
+        CubeFace::Front
+    } else {
+        CubeFace::Back
+    }
+}
+
+pub fn cube_uv(face: CubeFace, p: &Point) -> (f64, f64) {
+    let (u, v) = match face {
+        CubeFace::Front => (p.x + 1.0, p.y + 1.0),
+        CubeFace::Back => (1.0 - p.x, p.y + 1.0),
+        CubeFace::Left => (p.z + 1.0, p.y + 1.0),
+        CubeFace::Right => (1.0 - p.z, p.y + 1.0),
+        CubeFace::Up => (p.x + 1.0, 1.0 - p.z),
+        CubeFace::Down => (p.x + 1.0, p.z + 1.0),
+    };
+
+    (u.rem_euclid(2.0) / 2.0, v.rem_euclid(2.0) / 2.0)
+}
+
+pub fn cube_map(p: &Point) -> (f64, f64) {
+    cube_uv(face_from_point(p), p)
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use super::*;
+
+    fn assert_uv(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            approx_eq!(f64, actual.0, expected.0, epsilon = 0.0001)
+                && approx_eq!(f64, actual.1, expected.1, epsilon = 0.0001),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn spherical_mapping_on_3d_point() {
+        let examples = vec![
+            (Point::new(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (Point::new(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (Point::new(0.0, 0.0, 1.0), (0.5, 0.5)),
+            (Point::new(-1.0, 0.0, 0.0), (0.75, 0.5)),
+            (Point::new(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (Point::new(0.0, -1.0, 0.0), (0.5, 0.0)),
+            (
+                Point::new(
+                    std::f64::consts::FRAC_1_SQRT_2,
+                    std::f64::consts::FRAC_1_SQRT_2,
+                    0.0,
+                ),
+                (0.25, 0.75),
+            ),
+        ];
+        for (point, uv) in examples {
+            assert_uv(spherical_map(&point), uv);
+        }
+    }
+
+    #[test]
+    fn planar_mapping_on_3d_point() {
+        let examples = vec![
+            (Point::new(0.25, 0.0, 0.5), (0.25, 0.5)),
+            (Point::new(0.25, 0.0, -0.25), (0.25, 0.75)),
+            (Point::new(0.25, 0.5, -0.25), (0.25, 0.75)),
+            (Point::new(1.25, 0.0, 0.5), (0.25, 0.5)),
+            (Point::new(0.25, 0.0, -1.75), (0.25, 0.25)),
+            (Point::new(1.0, 0.0, -1.0), (0.0, 0.0)),
+            (Point::new(0.0, 0.0, 0.0), (0.0, 0.0)),
+        ];
+        for (point, uv) in examples {
+            assert_uv(planar_map(&point), uv);
+        }
+    }
+
+    #[test]
+    fn cylindrical_mapping_on_3d_point() {
+        let examples = vec![
+            (Point::new(0.0, 0.0, -1.0), (0.0, 0.0)),
+            (Point::new(0.0, 0.5, -1.0), (0.0, 0.5)),
+            (Point::new(0.0, 1.0, -1.0), (0.0, 0.0)),
+            (Point::new(0.70711, 0.5, -0.70711), (0.125, 0.5)),
+            (Point::new(1.0, 0.5, 0.0), (0.25, 0.5)),
+            (Point::new(0.70711, 0.5, 0.70711), (0.375, 0.5)),
+            (Point::new(0.0, -0.25, 1.0), (0.5, 0.75)),
+            (Point::new(-0.70711, 0.5, 0.70711), (0.625, 0.5)),
+            (Point::new(-1.0, 1.25, 0.0), (0.75, 0.25)),
+            (Point::new(-0.70711, 0.5, -0.70711), (0.875, 0.5)),
+        ];
+        for (point, uv) in examples {
+            assert_uv(cylindrical_map(&point), uv);
+        }
+    }
+
+    #[test]
+    fn identifying_face_of_cube_from_point() {
+        let examples = vec![
+            (Point::new(-1.0, 0.5, -0.25), CubeFace::Left),
+            (Point::new(1.1, -0.75, 0.8), CubeFace::Right),
+            (Point::new(0.1, 0.6, 0.9), CubeFace::Front),
+            (Point::new(-0.7, 0.0, -2.0), CubeFace::Back),
+            (Point::new(0.5, 1.0, 0.9), CubeFace::Up),
+            (Point::new(-0.2, -1.3, 1.1), CubeFace::Down),
+        ];
+        for (point, face) in examples {
+            assert_eq!(face_from_point(&point), face);
+        }
+    }
+
+    #[test]
+    fn uv_mapping_faces_of_cube() {
+        let examples = vec![
+            (CubeFace::Front, Point::new(-0.5, 0.5, 1.0), (0.25, 0.75)),
+            (CubeFace::Front, Point::new(0.5, -0.5, 1.0), (0.75, 0.25)),
+            (CubeFace::Back, Point::new(0.5, 0.5, -1.0), (0.25, 0.75)),
+            (CubeFace::Back, Point::new(-0.5, -0.5, -1.0), (0.75, 0.25)),
+            (CubeFace::Left, Point::new(-1.0, 0.5, -0.5), (0.25, 0.75)),
+            (CubeFace::Left, Point::new(-1.0, -0.5, 0.5), (0.75, 0.25)),
+            (CubeFace::Right, Point::new(1.0, 0.5, 0.5), (0.25, 0.75)),
+            (CubeFace::Right, Point::new(1.0, -0.5, -0.5), (0.75, 0.25)),
+            (CubeFace::Up, Point::new(-0.5, 1.0, -0.5), (0.25, 0.75)),
+            (CubeFace::Up, Point::new(0.5, 1.0, 0.5), (0.75, 0.25)),
+            (CubeFace::Down, Point::new(-0.5, -1.0, 0.5), (0.25, 0.75)),
+            (CubeFace::Down, Point::new(0.5, -1.0, -0.5), (0.75, 0.25)),
+        ];
+        for (face, point, uv) in examples {
+            assert_uv(cube_uv(face, &point), uv);
+        }
+    }
+
+    #[test]
+    fn spherical_mapping_of_centre_is_finite() {
+        assert_uv(spherical_map(&Point::new(0.0, 0.0, 0.0)), (0.5, 0.5));
+        assert_uv(UvMapping::Shape.map(&Point::new(0.0, 0.0, 0.0)), (0.5, 0.5));
+    }
+
+    #[test]
+    fn disc_mapping_covers_unit_square() {
+        assert_uv(disc_map(&Point::new(0.0, 1.0, 0.0), 1.0), (0.5, 0.5));
+        assert_uv(disc_map(&Point::new(-1.0, 1.0, 1.0), 1.0), (0.0, 0.0));
+        assert_uv(disc_map(&Point::new(1.0, 1.0, -1.0), 1.0), (1.0, 1.0));
+        assert_uv(disc_map(&Point::new(0.5, 0.5, 0.0), 0.5), (1.0, 0.5));
+    }
+}
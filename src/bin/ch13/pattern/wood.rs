@@ -0,0 +1,109 @@
+use crate::{
+    matrix::Matrix,
+    noise::Perlin,
+    tuple::{Color, Point},
+};
+
+use super::{ColorRamp, Pattern};
+
+/// Growth rings around the y axis, bent by turbulence so they look grown.
+#[derive(Debug)]
+pub struct Wood {
+    pub ramp: ColorRamp,
+    /// Rings per unit of distance from the axis.
+    pub ring_spacing: f64,
+    /// How far, in rings, the noise can push a ring.
+    pub turbulence: f64,
+    pub octaves: u32,
+    pub perlin: Perlin,
+    pub transform: Matrix<4>,
+}
+
+impl Wood {
+    pub fn new(a: Color, b: Color) -> Self {
+        Self {
+            ramp: ColorRamp::between(a, b),
+            ring_spacing: 1.0,
+            turbulence: 0.3,
+            octaves: 4,
+            perlin: Perlin::default(),
+            transform: Default::default(),
+        }
+    }
+
+    /// Position within the current ring, from 0 at its inner edge to 1.
+    pub fn value_at(&self, point: &Point) -> f64 {
+        let distance = (point.x.powi(2) + point.z.powi(2)).sqrt() * self.ring_spacing;
+        let wobble = self.turbulence * self.perlin.turbulence(point, self.octaves, 2.0, 0.5);
+        let rings = distance + wobble;
+        rings - rings.floor()
+    }
+}
+
+impl Pattern for Wood {
+    fn pattern_at(&self, point: &Point) -> Color {
+        let point = self.transform.inverse() * *point;
+        let t = self.value_at(&point);
+        self.ramp.color_at(t)
+    }
+
+    fn transform(&self) -> Matrix<4> {
+        self.transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pattern::ramp::Interpolation, tuple::Tuple};
+
+    use super::*;
+
+    #[test]
+    fn untroubled_wood_is_concentric_rings() {
+        let mut pattern = Wood::new(Color::BLACK, Color::WHITE);
+        pattern.turbulence = 0.0;
+        pattern.ring_spacing = 2.0;
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.25, 3.0, 0.0)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.0, -1.0, 0.25)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(pattern.pattern_at(&Point::new(0.6, 0.0, 0.8)), Color::BLACK);
+    }
+
+    #[test]
+    fn turbulence_bends_the_rings() {
+        let mut straight = Wood::new(Color::BLACK, Color::WHITE);
+        straight.turbulence = 0.0;
+        let bent = Wood::new(Color::BLACK, Color::WHITE);
+        let differs = (0..20).any(|i| {
+            let p = Point::new(0.3 + i as f64 * 0.11, i as f64 * 0.07, 0.2);
+            straight.pattern_at(&p) != bent.pattern_at(&p)
+        });
+        assert!(differs);
+    }
+
+    #[test]
+    fn wood_rings_follow_the_ramp() {
+        let mut pattern = Wood::new(Color::BLACK, Color::WHITE);
+        pattern.turbulence = 0.0;
+        pattern.ramp = ColorRamp::new(vec![
+            (0.0, Color::BLACK),
+            (0.8, Color::new(0.6, 0.4, 0.2)),
+            (1.0, Color::BLACK),
+        ]);
+        pattern.ramp.interpolation = Interpolation::Constant;
+        assert_eq!(
+            pattern.pattern_at(&Point::new(0.9, 0.0, 0.0)),
+            Color::new(0.6, 0.4, 0.2)
+        );
+        assert_eq!(pattern.pattern_at(&Point::new(1.5, 0.0, 0.0)), Color::BLACK);
+    }
+}
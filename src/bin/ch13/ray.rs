@@ -1,23 +1,43 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, f64::consts::PI, rc::Rc};
 
 use float_cmp::approx_eq;
 
 use crate::world::{Computations, World};
+use crate::{materials::ThinFilm, pattern::Pattern, shape::Shape};
 use crate::{
     matrix::Matrix,
-    tuple::{Point, Tuple, Vector},
+    tuple::{Color, Point, Tuple, Vector},
 };
-use crate::{pattern::Pattern, shape::Shape};
 
 #[derive(Debug)]
 pub struct Ray {
     pub origin: Point,
     pub direction: Vector,
+    /// Rays through the neighbouring pixels, when the ray came from a camera.
+    pub differentials: Option<Differentials>,
+}
+
+/// The rays one pixel over in x and in y, traced alongside a main ray to
+/// estimate how much surface a pixel covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Differentials {
+    pub rx_origin: Point,
+    pub rx_direction: Vector,
+    pub ry_origin: Point,
+    pub ry_direction: Vector,
 }
 
 impl Ray {
     pub fn new(origin: Point, direction: Vector) -> Ray {
-        Ray { origin, direction }
+        Ray {
+            origin,
+            direction,
+            differentials: None,
+        }
+    }
+    pub fn with_differentials(mut self, differentials: Option<Differentials>) -> Self {
+        self.differentials = differentials;
+        self
     }
     pub fn position(&self, t: f64) -> Point {
         self.origin + self.direction * t
@@ -26,6 +46,12 @@ impl Ray {
         Self {
             origin: t * self.origin,
             direction: t * self.direction,
+            differentials: self.differentials.map(|d| Differentials {
+                rx_origin: t * d.rx_origin,
+                rx_direction: t * d.rx_direction,
+                ry_origin: t * d.ry_origin,
+                ry_direction: t * d.ry_direction,
+            }),
         }
     }
     pub fn intersect(&self, shape: Rc<RefCell<dyn Shape>>) -> Vec<Intersection> {
@@ -86,6 +112,166 @@ pub fn schlick(comps: &Computations) -> f64 {
 
     r0 + (1.0 - r0) * (1.0 - cos).powi(5)
 }
+
+/// Wavelengths in nanometres that stand in for the red, green and blue channels.
+const RGB_WAVELENGTHS: [f64; 3] = [650.0, 510.0, 475.0];
+
+/// Reflectance of a surface under a thin film, one colour channel at a time.
+///
+/// The film sits between the medium the ray arrives from (`n1`) and the
+/// material beneath it (`n2`).
+pub fn thin_film_fresnel(comps: &Computations, film: ThinFilm) -> Color {
+    let cos = comps.eyev.dot(comps.normalv).clamp(0.0, 1.0);
+    let [red, green, blue] = RGB_WAVELENGTHS
+        .map(|wavelength| thin_film_reflectance(cos, comps.n1, film, comps.n2, wavelength));
+    Color::new(red, green, blue)
+}
+
+/// Reflectance of a metal under a thin film, such as an oxide layer or a lacquer.
+pub fn coated_conductor_fresnel(
+    comps: &Computations,
+    film: ThinFilm,
+    eta: Color,
+    k: Color,
+) -> Color {
+    let cos = comps.eyev.dot(comps.normalv).clamp(0.0, 1.0);
+    let [red, green, blue] = [
+        (RGB_WAVELENGTHS[0], eta.red, k.red),
+        (RGB_WAVELENGTHS[1], eta.green, k.green),
+        (RGB_WAVELENGTHS[2], eta.blue, k.blue),
+    ]
+    .map(|(wavelength, eta, k)| {
+        film_reflectance(cos, comps.n1, film, Complex::new(eta, k), wavelength)
+    });
+    Color::new(red, green, blue)
+}
+
+/// Airy reflectance of a single film, averaged over both polarisations.
+pub fn thin_film_reflectance(cos1: f64, n1: f64, film: ThinFilm, n3: f64, wavelength: f64) -> f64 {
+    film_reflectance(cos1, n1, film, Complex::new(n3, 0.0), wavelength)
+}
+
+/// Airy reflectance of a film over a substrate with a complex index `n3`.
+///
+/// Products `n3 * cos3` are taken as `sqrt(n3² - n1² sin²θ1)` so that
+/// absorbing and totally reflecting substrates need no special case.
+fn film_reflectance(cos1: f64, n1: f64, film: ThinFilm, n3: Complex, wavelength: f64) -> f64 {
+    let sin2_1 = 1.0 - cos1 * cos1;
+    let sin2_f = (n1 / film.ior).powi(2) * sin2_1;
+    if sin2_f > 1.0 {
+        return 1.0;
+    }
+    let cosf = (1.0 - sin2_f).sqrt();
+    let nf = film.ior;
+
+    let phase = 4.0 * PI * nf * film.thickness * cosf / wavelength;
+    let airy = |r12: f64, r23: Complex| {
+        let r23_norm = r23.norm();
+        let cross = 2.0 * r12 * r23_norm * (phase + r23.arg()).cos();
+        (r12 * r12 + r23_norm * r23_norm + cross) / (1.0 + r12 * r12 * r23_norm * r23_norm + cross)
+    };
+
+    let n3_squared = n3.mul(n3);
+    let n3_cos3 = n3_squared.sub(Complex::new(n1 * n1 * sin2_1, 0.0)).sqrt();
+    let nf_cosf = Complex::new(nf * cosf, 0.0);
+
+    let r12s = (n1 * cos1 - nf * cosf) / (n1 * cos1 + nf * cosf);
+    let r23s = nf_cosf.sub(n3_cos3).div(nf_cosf.add(n3_cos3));
+    let r12p = (nf * cos1 - n1 * cosf) / (nf * cos1 + n1 * cosf);
+    let r23p = n3_squared
+        .scale(cosf)
+        .sub(n3_cos3.scale(nf))
+        .div(n3_squared.scale(cosf).add(n3_cos3.scale(nf)));
+
+    0.5 * (airy(r12s, r23s) + airy(r12p, r23p))
+}
+
+/// Just enough complex arithmetic for the film's substrate reflection.
+#[derive(Clone, Copy, Debug)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+
+    fn div(self, other: Self) -> Self {
+        let denom = other.re * other.re + other.im * other.im;
+        Self::new(
+            (self.re * other.re + self.im * other.im) / denom,
+            (self.im * other.re - self.re * other.im) / denom,
+        )
+    }
+
+    fn scale(self, factor: f64) -> Self {
+        Self::new(self.re * factor, self.im * factor)
+    }
+
+    fn norm(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    /// Principal square root, with a non-negative imaginary part for `im >= 0`.
+    fn sqrt(self) -> Self {
+        let norm = self.norm();
+        let re = (0.5 * (norm + self.re)).max(0.0).sqrt();
+        let im = (0.5 * (norm - self.re)).max(0.0).sqrt();
+        Self::new(re, if self.im < 0.0 { -im } else { im })
+    }
+}
+
+/// Unpolarised reflectance of a metal, one colour channel at a time.
+pub fn conductor_fresnel(comps: &Computations, eta: Color, k: Color) -> Color {
+    let cos = comps.eyev.dot(comps.normalv).clamp(0.0, 1.0);
+    Color::new(
+        conductor_reflectance(cos, eta.red, k.red),
+        conductor_reflectance(cos, eta.green, k.green),
+        conductor_reflectance(cos, eta.blue, k.blue),
+    )
+}
+
+/// Exact Fresnel equations for a complex refractive index `eta + ik` seen from air.
+pub fn conductor_reflectance(cos: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos * cos;
+    let sin2 = 1.0 - cos2;
+    let eta2 = eta * eta;
+    let k2 = k * k;
+
+    let t0 = eta2 - k2 - sin2;
+    let a2_plus_b2 = (t0 * t0 + 4.0 * eta2 * k2).sqrt();
+    let t1 = a2_plus_b2 + cos2;
+    let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t2 = 2.0 * cos * a;
+    let rs = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+
+    0.5 * (rp + rs)
+}
 #[cfg(test)]
 mod tests {
     use std::f64::{consts::FRAC_1_SQRT_2, EPSILON};
@@ -381,4 +567,120 @@ mod tests {
         let reflectance = schlick(&comps);
         assert!(approx_eq!(f64, reflectance, 0.48873, epsilon = 0.00003));
     }
+
+    #[test]
+    fn conductor_reflectance_at_normal_incidence() {
+        let (eta, k) = (0.2, 3.9);
+        let expected = ((eta - 1.0_f64).powi(2) + k * k) / ((eta + 1.0_f64).powi(2) + k * k);
+        assert!(approx_eq!(
+            f64,
+            conductor_reflectance(1.0, eta, k),
+            expected,
+            epsilon = 0.00003
+        ));
+    }
+
+    #[test]
+    fn conductor_reflectance_at_grazing_angle_is_total() {
+        assert!(approx_eq!(
+            f64,
+            conductor_reflectance(0.0, 1.1, 2.1),
+            1.0,
+            epsilon = 0.00003
+        ));
+    }
+
+    #[test]
+    fn conductor_without_absorption_matches_dielectric() {
+        let n: f64 = 1.5;
+        let r0 = ((n - 1.0) / (n + 1.0)).powi(2);
+        assert!(approx_eq!(
+            f64,
+            conductor_reflectance(1.0, n, 0.0),
+            r0,
+            epsilon = 0.00003
+        ));
+    }
+
+    #[test]
+    fn gold_reflects_more_red_than_blue() {
+        let shape = Rc::new(RefCell::new(Sphere::new(0)));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = intersections(vec![
+            Intersection::new(4.0, shape.clone()),
+            Intersection::new(6.0, shape),
+        ]);
+        let comps = prepare_computations(&xs[0], &r, &xs);
+        let reflectance = conductor_fresnel(
+            &comps,
+            crate::materials::Fresnel::GOLD_ETA,
+            crate::materials::Fresnel::GOLD_K,
+        );
+        assert!(reflectance.red > 0.9);
+        assert!(reflectance.red > reflectance.green);
+        assert!(reflectance.green > reflectance.blue);
+    }
+
+    #[test]
+    fn copper_is_red_and_silver_is_neutral_at_normal_incidence() {
+        let reflectance = |fresnel| match fresnel {
+            crate::materials::Fresnel::Conductor { eta, k } => Color::new(
+                conductor_reflectance(1.0, eta.red, k.red),
+                conductor_reflectance(1.0, eta.green, k.green),
+                conductor_reflectance(1.0, eta.blue, k.blue),
+            ),
+            _ => unreachable!(),
+        };
+        let copper = reflectance(crate::materials::Fresnel::COPPER);
+        assert!(copper.red > 0.9);
+        assert!(copper.red - copper.blue > 0.4);
+        let silver = reflectance(crate::materials::Fresnel::SILVER);
+        assert!(silver.red > 0.9 && silver.green > 0.9 && silver.blue > 0.9);
+        assert!(silver.red - silver.blue < 0.1);
+    }
+
+    #[test]
+    fn film_of_zero_thickness_is_invisible() {
+        let film = ThinFilm::new(0.0, 1.33);
+        let n: f64 = 1.5;
+        let r0 = ((1.0 - n) / (1.0 + n)).powi(2);
+        assert!(approx_eq!(
+            f64,
+            thin_film_reflectance(1.0, 1.0, film, n, 510.0),
+            r0,
+            epsilon = 0.00003
+        ));
+    }
+
+    #[test]
+    fn quarter_wave_soap_film_reflects_that_wavelength_most() {
+        let film = ThinFilm::new(510.0 / (4.0 * 1.33), 1.33);
+        let red = thin_film_reflectance(1.0, 1.0, film, 1.0, 650.0);
+        let green = thin_film_reflectance(1.0, 1.0, film, 1.0, 510.0);
+        let blue = thin_film_reflectance(1.0, 1.0, film, 1.0, 475.0);
+        assert!(green > red);
+        assert!(green > blue);
+    }
+
+    #[test]
+    fn thin_film_colour_shifts_with_view_angle() {
+        let film = ThinFilm::new(400.0, 1.33);
+        let head_on = thin_film_reflectance(1.0, 1.0, film, 1.0, 510.0);
+        let oblique = thin_film_reflectance(0.5, 1.0, film, 1.0, 510.0);
+        assert!(!approx_eq!(f64, head_on, oblique, epsilon = 0.001));
+    }
+
+    #[test]
+    fn film_of_zero_thickness_over_a_conductor_is_invisible() {
+        let film = ThinFilm::new(0.0, 1.5);
+        for cos in [1.0, 0.5] {
+            let coated = film_reflectance(cos, 1.0, film, Complex::new(0.18, 3.0), 510.0);
+            assert!(approx_eq!(
+                f64,
+                coated,
+                conductor_reflectance(cos, 0.18, 3.0),
+                epsilon = 0.00003
+            ));
+        }
+    }
 }
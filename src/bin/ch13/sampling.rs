@@ -0,0 +1,86 @@
+use std::f64::consts::PI;
+
+use crate::tuple::{Tuple, Vector};
+
+/// The `i`th of `n` points of the Hammersley set on the unit square.
+///
+/// The points are deterministic, so renders and tests are reproducible.
+pub fn hammersley(i: usize, n: usize) -> (f64, f64) {
+    let u = (i as f64 + 0.5) / n as f64;
+    let v = (i as u32).reverse_bits() as f64 / 4_294_967_296.0;
+    (u, v)
+}
+
+/// Two unit vectors perpendicular to `n` and to each other.
+pub fn orthonormal_basis(n: Vector) -> (Vector, Vector) {
+    let helper = if n.x.abs() > 0.9 {
+        Vector::new(0.0, 1.0, 0.0)
+    } else {
+        Vector::new(1.0, 0.0, 0.0)
+    };
+    let tangent = helper.cross(n).normalize();
+    let bitangent = n.cross(tangent);
+    (tangent, bitangent)
+}
+
+/// A microfacet normal around `normal` distributed by GGX with width `alpha`.
+pub fn sample_ggx(normal: Vector, alpha: f64, u1: f64, u2: f64) -> Vector {
+    let theta = (alpha * (u1 / (1.0 - u1)).sqrt()).atan();
+    let phi = 2.0 * PI * u2;
+    let (tangent, bitangent) = orthonormal_basis(normal);
+
+    (tangent * (theta.sin() * phi.cos())
+        + bitangent * (theta.sin() * phi.sin())
+        + normal * theta.cos())
+    .normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn hammersley_points_lie_in_unit_square() {
+        for i in 0..16 {
+            let (u, v) = hammersley(i, 16);
+            assert!((0.0..1.0).contains(&u));
+            assert!((0.0..1.0).contains(&v));
+        }
+    }
+
+    #[test]
+    fn hammersley_second_coordinate_is_radical_inverse() {
+        assert_eq!(hammersley(0, 4).1, 0.0);
+        assert_eq!(hammersley(1, 4).1, 0.5);
+        assert_eq!(hammersley(2, 4).1, 0.25);
+        assert_eq!(hammersley(3, 4).1, 0.75);
+    }
+
+    #[test]
+    fn basis_is_orthonormal() {
+        let n = Vector::new(1.0, 2.0, 3.0).normalize();
+        let (t, b) = orthonormal_basis(n);
+        assert!(approx_eq!(f64, t.dot(n), 0.0, epsilon = 0.00003));
+        assert!(approx_eq!(f64, b.dot(n), 0.0, epsilon = 0.00003));
+        assert!(approx_eq!(f64, t.dot(b), 0.0, epsilon = 0.00003));
+        assert!(approx_eq!(f64, b.magnitude(), 1.0, epsilon = 0.00003));
+    }
+
+    #[test]
+    fn tiny_alpha_sample_stays_on_normal() {
+        let n = Vector::new(0.0, 1.0, 0.0);
+        let h = sample_ggx(n, 0.0, 0.7, 0.3);
+        assert_eq!(h, n);
+    }
+
+    #[test]
+    fn ggx_samples_stay_in_upper_hemisphere() {
+        let n = Vector::new(0.0, 0.0, -1.0);
+        for i in 0..32 {
+            let (u1, u2) = hammersley(i, 32);
+            assert!(sample_ggx(n, 0.8, u1, u2).dot(n) > 0.0);
+        }
+    }
+}
@@ -5,6 +5,7 @@ use float_cmp::approx_eq;
 use crate::{
     materials::Material,
     matrix::Matrix,
+    pattern::uv::{cylindrical_map, disc_map},
     ray::Ray,
     tuple::{Tuple, Vector},
 };
@@ -103,6 +104,20 @@ impl Shape for Cone {
         }
     }
 
+    fn local_tangent_at(&self, p: &crate::tuple::Point) -> Vector {
+        super::around_y_axis(p)
+    }
+
+    fn local_uv_at(&self, p: &crate::tuple::Point) -> (f64, f64) {
+        let dist = p.x.powi(2) + p.z.powi(2);
+        let on_cap = p.y >= self.maximum - 0.00003 || p.y <= self.minimum + 0.00003;
+        if on_cap && dist < p.y.powi(2) {
+            disc_map(p, p.y.abs())
+        } else {
+            cylindrical_map(p)
+        }
+    }
+
     fn local_intersect(&self, r: &crate::ray::Ray) -> Vec<f64> {
         let mut xs = vec![];
         let a = r.direction.x.powi(2) - r.direction.y.powi(2) + r.direction.z.powi(2);
@@ -212,6 +227,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn texture_coordinates_on_cone_sides_and_caps() {
+        let mut shape = Cone::new(0);
+        shape.minimum = -1.0;
+        shape.maximum = 1.0;
+        shape.closed = true;
+        let examples = vec![
+            (Point::new(0.0, -0.5, -0.5), (0.0, 0.5)),
+            (Point::new(0.5, -0.5, 0.0), (0.25, 0.5)),
+            (Point::new(0.5, -1.0, 0.0), (0.75, 0.5)),
+            (Point::new(0.0, 1.0, 0.5), (0.5, 0.25)),
+        ];
+        for (point, uv) in examples {
+            assert_eq!(shape.local_uv_at(&point), uv);
+        }
+    }
+
     #[test]
     fn computing_normal_vector_on_cone() {
         let shape = Cone::new(0);
@@ -3,6 +3,7 @@ use std::f64::INFINITY;
 use crate::{
     materials::Material,
     matrix::Matrix,
+    pattern::uv::cube_map,
     tuple::{Point, Tuple, Vector},
 };
 
@@ -83,6 +84,20 @@ impl Shape for Cube {
         }
     }
 
+    fn local_tangent_at(&self, p: &crate::tuple::Point) -> Vector {
+        let maxc = p.x.abs().max(p.y.abs()).max(p.z.abs());
+
+        if maxc == p.x.abs() {
+            Vector::new(0.0, 0.0, 1.0)
+        } else {
+            Vector::new(1.0, 0.0, 0.0)
+        }
+    }
+
+    fn local_uv_at(&self, p: &crate::tuple::Point) -> (f64, f64) {
+        cube_map(p)
+    }
+
     fn local_intersect(&self, r: &crate::ray::Ray) -> Vec<f64> {
         let (xtmin, xtmax) = check_axis(r.origin.x, r.direction.x);
         let (ytmin, ytmax) = check_axis(r.origin.y, r.direction.y);
@@ -208,4 +223,22 @@ mod tests {
             assert_eq!(n, normal);
         }
     }
+
+    #[test]
+    fn tangent_lies_in_each_face() {
+        let c = Cube::new(0);
+        let points = vec![
+            Point::new(1.0, 0.5, -0.8),
+            Point::new(-1.0, -0.2, 0.9),
+            Point::new(-0.4, 1.0, -0.1),
+            Point::new(0.3, -1.0, -0.7),
+            Point::new(-0.6, 0.3, 1.0),
+            Point::new(0.4, 0.4, -1.0),
+        ];
+        for point in points {
+            let t = c.local_tangent_at(&point);
+            assert_eq!(c.local_normal_at(&point).dot(t), 0.0);
+            assert_eq!(t.magnitude(), 1.0);
+        }
+    }
 }
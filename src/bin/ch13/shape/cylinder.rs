@@ -5,6 +5,7 @@ use float_cmp::approx_eq;
 use crate::{
     materials::Material,
     matrix::Matrix,
+    pattern::uv::{cylindrical_map, disc_map},
     ray::Ray,
     tuple::{Tuple, Vector},
 };
@@ -100,6 +101,19 @@ impl Shape for Cylinder {
         }
     }
 
+    fn local_tangent_at(&self, p: &crate::tuple::Point) -> Vector {
+        super::around_y_axis(p)
+    }
+
+    fn local_uv_at(&self, p: &crate::tuple::Point) -> (f64, f64) {
+        let dist = p.x.powi(2) + p.z.powi(2);
+        if dist < 1.0 && (p.y >= self.maximum - 0.00003 || p.y <= self.minimum + 0.00003) {
+            disc_map(p, 1.0)
+        } else {
+            cylindrical_map(p)
+        }
+    }
+
     fn local_intersect(&self, r: &crate::ray::Ray) -> Vec<f64> {
         let mut xs = vec![];
         let a = r.direction.x.powi(2) + r.direction.z.powi(2);
@@ -285,4 +299,35 @@ mod tests {
             assert_eq!(n, normal);
         }
     }
+
+    #[test]
+    fn texture_coordinates_on_cylinder_sides_and_caps() {
+        let mut cyl = Cylinder::new(0);
+        cyl.minimum = 0.0;
+        cyl.maximum = 1.0;
+        cyl.closed = true;
+        let examples = vec![
+            (Point::new(0.0, 0.25, -1.0), (0.0, 0.25)),
+            (Point::new(1.0, 0.5, 0.0), (0.25, 0.5)),
+            (Point::new(0.5, 1.0, 0.0), (0.75, 0.5)),
+            (Point::new(0.0, 0.0, -0.5), (0.5, 0.75)),
+        ];
+        for (point, uv) in examples {
+            assert_eq!(cyl.local_uv_at(&point), uv);
+        }
+    }
+
+    #[test]
+    fn tangent_runs_around_cylinder() {
+        let c = Cylinder::new(0);
+        let examples = vec![
+            (Point::new(1.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0)),
+            (Point::new(0.0, 5.0, -1.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(-1.0, 1.0, 0.0), Vector::new(0.0, 0.0, -1.0)),
+        ];
+        for (point, tangent) in examples {
+            assert_eq!(c.local_tangent_at(&point), tangent);
+            assert_eq!(c.local_normal_at(&point).dot(tangent), 0.0);
+        }
+    }
 }
@@ -14,8 +14,9 @@ pub use sphere::Sphere;
 use crate::{
     materials::Material,
     matrix::Matrix,
-    pattern::Pattern,
+    pattern::{uv::spherical_map, Pattern},
     ray::{Intersection, Ray},
+    sampling::orthonormal_basis,
     tuple::{Point, Tuple, Vector},
 };
 
@@ -37,6 +38,40 @@ pub trait Shape: Debug {
 
         world_normal.normalize()
     }
+
+    /// Direction along the surface that anisotropic materials are brushed in.
+    fn local_tangent_at(&self, p: &Point) -> Vector {
+        orthonormal_basis(self.local_normal_at(p).normalize()).0
+    }
+
+    /// Converts a world space point into this shape's object space.
+    ///
+    /// Shapes nested in groups would fold their parents' transforms in here.
+    fn world_to_object(&self, p: &Point) -> Point {
+        self.get_transform().inverse() * *p
+    }
+
+    /// Texture coordinates of a point on the surface, in object space.
+    fn local_uv_at(&self, p: &Point) -> (f64, f64) {
+        spherical_map(p)
+    }
+
+    fn tangent_at(&self, p: Point) -> Vector {
+        let local_point = self.get_transform().inverse() * p;
+        let local_tangent = self.local_tangent_at(&local_point);
+        let world_tangent = self.get_transform() * local_tangent;
+
+        world_tangent.normalize()
+    }
+}
+
+/// Tangent running around the y axis, shared by the round shapes.
+pub(crate) fn around_y_axis(p: &Point) -> Vector {
+    if p.x.abs() < 0.00003 && p.z.abs() < 0.00003 {
+        Vector::new(1.0, 0.0, 0.0)
+    } else {
+        Vector::new(-p.z, 0.0, p.x).normalize()
+    }
 }
 
 impl PartialEq for dyn Shape {
@@ -223,4 +258,21 @@ mod tests {
             .normal_at(Point::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
         assert_eq!(n, Vector::new(0.0, 0.97014, -0.24254));
     }
+
+    #[test]
+    fn default_tangent_is_perpendicular_to_normal() {
+        let s = TestShape::new(0);
+        let p = Point::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2);
+        let t = s.tangent_at(p);
+        assert!(t.dot(s.normal_at(p)).abs() < 0.00003);
+        assert_eq!(t, t.normalize());
+    }
+
+    #[test]
+    fn tangent_of_transformed_shape() {
+        let mut s = Cylinder::new(0);
+        s.transform = Matrix::<4>::IDENTITY.rotation_z(PI / 2.0);
+        let t = s.tangent_at(Point::new(0.0, 0.0, -1.0));
+        assert_eq!(t, Vector::new(0.0, 1.0, 0.0));
+    }
 }
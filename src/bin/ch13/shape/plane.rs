@@ -5,7 +5,7 @@ use float_cmp::approx_eq;
 use crate::{
     materials::Material,
     matrix::Matrix,
-    pattern::Pattern,
+    pattern::{uv::planar_map, Pattern},
     tuple::{Tuple, Vector},
 };
 
@@ -62,6 +62,14 @@ impl Shape for Plane {
     fn get_mut_material(&mut self) -> &mut Material {
         &mut self.material
     }
+
+    fn local_tangent_at(&self, _p: &crate::tuple::Point) -> Vector {
+        Vector::new(1.0, 0.0, 0.0)
+    }
+
+    fn local_uv_at(&self, p: &crate::tuple::Point) -> (f64, f64) {
+        planar_map(p)
+    }
 }
 #[cfg(test)]
 mod tests {
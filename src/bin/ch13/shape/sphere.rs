@@ -62,6 +62,10 @@ impl Shape for Sphere {
     fn get_mut_material(&mut self) -> &mut Material {
         &mut self.material
     }
+
+    fn local_tangent_at(&self, p: &Point) -> Vector {
+        super::around_y_axis(p)
+    }
 }
 impl Sphere {
     pub fn new(id: usize) -> Self {
@@ -96,6 +100,20 @@ mod tests {
     use super::*;
 
     const ROOT_3_OVER_3: f64 = 0.5773502692;
+    #[test]
+    fn texture_coordinates_on_sphere() {
+        let s = Sphere::new(0);
+        let examples = vec![
+            (Point::new(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (Point::new(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (Point::new(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (Point::new(0.0, -1.0, 0.0), (0.5, 0.0)),
+        ];
+        for (point, uv) in examples {
+            assert_eq!(s.local_uv_at(&point), uv);
+        }
+    }
+
     #[test]
     fn sphere_normal_on_x() {
         let s = Sphere::new(0);
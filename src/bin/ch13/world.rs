@@ -1,10 +1,16 @@
-use std::{cell::RefCell, rc::Rc, u32};
+use std::{cell::RefCell, f64::consts::PI, rc::Rc, u32};
 
 use crate::{
-    lights::PointLight,
-    materials::{lighting, Material},
+    background::Background,
+    lights::{EnvironmentLight, PointLight},
+    materials::{direct_lighting, Fresnel, IncomingLight, Material},
     matrix::Matrix,
-    ray::{hit, schlick, Intersection, Ray},
+    pattern::Footprint,
+    ray::{
+        coated_conductor_fresnel, conductor_fresnel, hit, schlick, thin_film_fresnel,
+        Differentials, Intersection, Ray,
+    },
+    sampling::{hammersley, sample_ggx},
     shape::{sphere::reflect, Shape, Sphere},
     transformations::Transformation,
     tuple::{Color, Point, Tuple, Vector},
@@ -12,9 +18,13 @@ use crate::{
 use std::assert_matches::assert_matches;
 
 const EPSILON: f64 = 0.00003;
+/// Rays traced for a rough reflection or refraction at the top of the recursion.
+const GLOSSY_SAMPLES: usize = 16;
 pub struct World {
     pub objects: Vec<Rc<RefCell<dyn Shape>>>,
     pub lights: Vec<PointLight>,
+    pub background: Background,
+    pub environment: Option<EnvironmentLight>,
 }
 
 impl World {
@@ -22,6 +32,8 @@ impl World {
         Self {
             objects: Vec::new(),
             lights: Vec::new(),
+            background: Background::default(),
+            environment: None,
         }
     }
 
@@ -31,99 +43,301 @@ impl World {
             let comps = prepare_computations(&i, ray, &xs);
             self.shade_hit(&comps, remaining)
         } else {
-            Color::BLACK
+            self.background.color_for(&ray.direction)
         }
     }
+    /// Hits along `r`, leaving out those cut away by an opacity mask.
     pub fn intersect(&self, r: &Ray) -> Vec<Intersection> {
         intersections(
             self.objects
                 .iter()
                 .map(|s| r.intersect(s.clone()))
                 .flatten()
+                .filter(|i| {
+                    let object = i.object.borrow();
+                    !object.get_material().is_cut_out(&*object, &r.position(i.t))
+                })
                 .collect(),
         )
     }
     pub fn is_shadowed(&self, light: &PointLight, point: &Point) -> bool {
-        let mut res = true;
         let v = light.position - *point;
-        let distance = v.magnitude();
-        let direction = v.normalize();
-        let r = Ray::new(*point, direction);
+        self.is_occluded(point, &v.normalize(), v.magnitude())
+    }
+    /// Whether an opaque object lies within `distance` of `point` along `direction`.
+    pub fn is_occluded(&self, point: &Point, direction: &Vector, distance: f64) -> bool {
+        let r = Ray::new(*point, *direction);
         let intersections = self
             .intersect(&r)
             .into_iter()
-            .filter(|i| !(i.object.borrow().get_material().transparency > 0_f64))
+            .filter(|i| {
+                let object = i.object.borrow();
+                let point = r.position(i.t);
+                !(object.get_material().transparency_at(&*object, &point) > 0_f64)
+            })
             .collect::<Vec<_>>();
-        res = res
-            && match hit(&intersections) {
-                Some(h) if h.t < distance => true,
-                _ => false,
+        match hit(&intersections) {
+            Some(h) if h.t < distance => true,
+            _ => false,
+        }
+    }
+    /// Diffuse and specular light from the environment map, if there is one.
+    fn environment_lighting(&self, material: &Material, comps: &Computations) -> Color {
+        let env = match &self.environment {
+            Some(env) => env,
+            None => return Color::BLACK,
+        };
+        let object = comps.object.borrow();
+        let color = material.filtered_color_at(&*object, &comps.over_point, &comps.footprint);
+
+        let mut res = Color::BLACK;
+        for i in 0..env.samples {
+            let (u1, u2) = hammersley(i, env.samples);
+            let (direction, radiance, pdf) = env.sample(u1, u2);
+            if direction.dot(comps.normalv) <= 0.0
+                || self.is_occluded(&comps.over_point, &direction, f64::INFINITY)
+            {
+                continue;
+            }
+            // lighting treats intensity as π times radiance times solid angle
+            let incoming = IncomingLight {
+                intensity: radiance * (1.0 / (PI * pdf * env.samples as f64)),
+                lightv: direction,
             };
+            res = res
+                + direct_lighting(
+                    material,
+                    &*object,
+                    color,
+                    &incoming,
+                    &comps.over_point,
+                    &comps.eyev,
+                    &comps.normalv,
+                );
+        }
 
         res
     }
     pub fn shade_hit(&self, comps: &Computations, remaining: u32) -> Color {
-        let mut res = Color::BLACK;
+        let object = comps.object.borrow();
+        self.shade_material(object.get_material(), comps, remaining)
+    }
+
+    /// Shades `material` and any layers over it, mixing each layer in by its
+    /// weight at the hit.
+    fn shade_material(&self, material: &Material, comps: &Computations, remaining: u32) -> Color {
+        let layer = match &material.layer {
+            Some(layer) => layer,
+            None => return self.shade_surface(material, comps, remaining),
+        };
+        let weight = layer.weight_at(&*comps.object.borrow(), &comps.over_point);
+        let base = if weight < 1.0 {
+            self.shade_surface(material, comps, remaining)
+        } else {
+            Color::BLACK
+        };
+        let top = if weight > 0.0 {
+            self.shade_material(&layer.material, comps, remaining)
+        } else {
+            Color::BLACK
+        };
+
+        base * (1.0 - weight) + top * weight
+    }
+
+    /// Shades a single material, ignoring its layers.
+    fn shade_surface(&self, material: &Material, comps: &Computations, remaining: u32) -> Color {
+        let object = comps.object.borrow();
+        let mut res = material.emitted() + self.environment_lighting(material, comps);
+        let color = material.filtered_color_at(&*object, &comps.over_point, &comps.footprint);
         for light in &self.lights {
-            let shadowed = self.is_shadowed(light, &comps.over_point);
-            let surface = lighting(
-                &comps.object.clone().borrow().get_material(),
-                &*comps.object.clone().borrow(),
-                &light,
-                &comps.over_point,
-                &comps.eyev,
-                &comps.normalv,
-                shadowed,
-            );
-            let reflected = self.reflected_color(comps, remaining);
-            let refracted = self.refracted_color(comps, remaining);
-
-            let reflective = comps.object.borrow().get_material().reflective;
-            let transparency = comps.object.borrow().get_material().transparency;
-
-            if reflective >= EPSILON && transparency >= EPSILON {
-                let reflectance = schlick(comps);
-                res = res + surface + reflected * reflectance + refracted * (1.0 - reflectance);
-            } else {
-                res = res + surface + reflected + refracted;
+            res = res + color * light.intensity * material.ambient;
+            if !self.is_shadowed(light, &comps.over_point) {
+                let incoming = IncomingLight::from_light(light, &comps.over_point);
+                res = res
+                    + direct_lighting(
+                        material,
+                        &*object,
+                        color,
+                        &incoming,
+                        &comps.over_point,
+                        &comps.eyev,
+                        &comps.normalv,
+                    );
             }
         }
 
-        res
+        // traced once per hit, whether or not there are point lights
+        let reflected = self.reflected_color_for(material, comps, remaining);
+        let refracted = self.refracted_color_for(material, comps, remaining);
+
+        let reflective = comps.reflective(material);
+        let transparency = comps.transparency(material);
+
+        if let Fresnel::Conductor { eta, k } = material.fresnel {
+            let reflectance = match material.thin_film {
+                Some(film) => coated_conductor_fresnel(comps, film, eta, k),
+                None => conductor_fresnel(comps, eta, k),
+            };
+            res + reflected * reflectance + refracted
+        } else if let Some(film) = material.thin_film {
+            let reflectance = thin_film_fresnel(comps, film);
+            res = res + reflected * reflectance;
+            if transparency >= EPSILON {
+                res = res + refracted * (Color::WHITE - reflectance);
+            }
+            res
+        } else if reflective >= EPSILON && transparency >= EPSILON {
+            let reflectance = schlick(comps);
+            res + reflected * reflectance + refracted * (1.0 - reflectance)
+        } else {
+            res + reflected + refracted
+        }
     }
 
     pub fn reflected_color(&self, comps: &Computations, remaining: u32) -> Color {
-        if comps.object.borrow().get_material().reflective == 0.0 || remaining <= 0 {
+        let object = comps.object.borrow();
+        self.reflected_color_for(object.get_material(), comps, remaining)
+    }
+
+    fn reflected_color_for(
+        &self,
+        material: &Material,
+        comps: &Computations,
+        remaining: u32,
+    ) -> Color {
+        let reflective = comps.reflective(material);
+        if reflective == 0.0 || remaining <= 0 {
             Color::BLACK
         } else {
-            let reflect_ray = Ray::new(comps.over_point, comps.reflectv);
-            let color = self.color_at(&reflect_ray, remaining - 1);
+            let roughness = material.roughness_at(&*comps.object.borrow(), &comps.over_point);
+            let color = if roughness > 0.0 {
+                self.glossy_reflection(comps, roughness, remaining)
+            } else {
+                let reflect_ray = Ray::new(comps.over_point, comps.reflectv)
+                    .with_differentials(comps.reflected_differentials());
+                self.color_at(&reflect_ray, remaining - 1)
+            };
+
+            color * reflective
+        }
+    }
+
+    /// Averages reflections about GGX distributed microfacet normals.
+    fn glossy_reflection(&self, comps: &Computations, roughness: f64, remaining: u32) -> Color {
+        let alpha = roughness * roughness;
+        let samples = glossy_samples(remaining);
+        let mut total = Color::BLACK;
+        let mut count = 0;
+        for i in 0..samples {
+            let (u1, u2) = hammersley(i, samples);
+            let microfacet = sample_ggx(comps.normalv, alpha, u1, u2);
+            let direction = reflect(-comps.eyev, microfacet);
+            if direction.dot(comps.normalv) <= 0.0 {
+                continue;
+            }
+            total = total + self.color_at(&Ray::new(comps.over_point, direction), remaining - 1);
+            count += 1;
+        }
 
-            color * comps.object.clone().borrow().get_material().reflective
+        if count == 0 {
+            self.color_at(&Ray::new(comps.over_point, comps.reflectv), remaining - 1)
+        } else {
+            total / count as f64
         }
     }
 
     pub fn refracted_color(&self, comps: &Computations, remaining: u32) -> Color {
-        if comps.object.borrow().get_material().transparency == 0.0 || remaining <= 0 {
+        let object = comps.object.borrow();
+        self.refracted_color_for(object.get_material(), comps, remaining)
+    }
+
+    fn refracted_color_for(
+        &self,
+        material: &Material,
+        comps: &Computations,
+        remaining: u32,
+    ) -> Color {
+        let transparency = comps.transparency(material);
+        if transparency == 0.0 || remaining <= 0 {
             return Color::BLACK;
         }
 
         let n_ratio = comps.n1 / comps.n2;
-        let cos_i = comps.eyev.dot(comps.normalv);
-        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
-        if sin2_t > 1.0 {
-            return Color::BLACK;
+        let roughness = material.refraction_roughness;
+        let color = if roughness > 0.0 {
+            self.frosted_refraction(comps, n_ratio, roughness, remaining)
+        } else {
+            match refract(comps.eyev, comps.normalv, n_ratio) {
+                Some(direction) => {
+                    let refract_ray = Ray::new(comps.under_point, direction)
+                        .with_differentials(comps.refracted_differentials(n_ratio));
+                    self.color_at(&refract_ray, remaining - 1)
+                }
+                None => return Color::BLACK,
+            }
+        };
+
+        color * transparency
+    }
+
+    /// Averages refractions through GGX distributed microfacet normals.
+    ///
+    /// Samples that are totally internally reflected contribute black, as the
+    /// single ideal ray does.
+    fn frosted_refraction(
+        &self,
+        comps: &Computations,
+        n_ratio: f64,
+        roughness: f64,
+        remaining: u32,
+    ) -> Color {
+        let alpha = roughness * roughness;
+        let samples = glossy_samples(remaining);
+        let mut total = Color::BLACK;
+        let mut count = 0;
+        for i in 0..samples {
+            let (u1, u2) = hammersley(i, samples);
+            let microfacet = sample_ggx(comps.normalv, alpha, u1, u2);
+            if comps.eyev.dot(microfacet) <= 0.0 {
+                continue;
+            }
+            count += 1;
+            match refract(comps.eyev, microfacet, n_ratio) {
+                Some(direction) if direction.dot(comps.normalv) < 0.0 => {
+                    let refract_ray = Ray::new(comps.under_point, direction);
+                    total = total + self.color_at(&refract_ray, remaining - 1);
+                }
+                _ => {}
+            }
         }
 
-        let cos_t = (1.0 - sin2_t).sqrt();
-        let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
-        let refract_ray = Ray::new(comps.under_point, direction);
+        if count == 0 {
+            Color::BLACK
+        } else {
+            total / count as f64
+        }
+    }
+}
 
-        let color = self.color_at(&refract_ray, remaining - 1)
-            * comps.object.borrow().get_material().transparency;
+/// Rays traced for a rough bounce with `remaining` bounces left.
+///
+/// The last bounces take 1 and then 4 samples, the rest `GLOSSY_SAMPLES`.
+/// Chained rough surfaces still multiply: four of them trace 16·16·4·1 rays.
+fn glossy_samples(remaining: u32) -> usize {
+    GLOSSY_SAMPLES.min(4usize.saturating_pow(remaining.saturating_sub(1)))
+}
 
-        color
+/// Snell's law, or `None` under total internal reflection.
+fn refract(eyev: Vector, normalv: Vector, n_ratio: f64) -> Option<Vector> {
+    let cos_i = eyev.dot(normalv);
+    let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+    if sin2_t > 1.0 {
+        return None;
     }
+
+    let cos_t = (1.0 - sin2_t).sqrt();
+    Some(normalv * (n_ratio * cos_i - cos_t) - eyev * n_ratio)
 }
 
 impl Default for World {
@@ -147,6 +361,8 @@ impl Default for World {
         Self {
             objects: vec![s1, s2],
             lights: vec![light],
+            background: Background::default(),
+            environment: None,
         }
     }
 }
@@ -168,6 +384,9 @@ pub struct Computations {
     pub under_point: Point,
     pub n1: f64,
     pub n2: f64,
+    /// The incoming ray's differentials, if it had any.
+    pub differentials: Option<Differentials>,
+    pub footprint: Option<Footprint>,
 }
 
 impl Computations {
@@ -196,8 +415,44 @@ impl Computations {
             under_point,
             n1,
             n2,
+            differentials: None,
+            footprint: None,
         }
     }
+
+    /// How reflective `material` is where the ray hit.
+    pub fn reflective(&self, material: &Material) -> f64 {
+        material.reflective_at(&*self.object.borrow(), &self.over_point)
+    }
+
+    /// How transparent `material` is where the ray hit.
+    pub fn transparency(&self, material: &Material) -> f64 {
+        material.transparency_at(&*self.object.borrow(), &self.over_point)
+    }
+
+    /// Differentials for the mirror reflection, each neighbouring ray bounced
+    /// off the same normal. Surface curvature is ignored.
+    pub fn reflected_differentials(&self) -> Option<Differentials> {
+        let (d, f) = (self.differentials?, self.footprint?);
+        Some(Differentials {
+            rx_origin: self.over_point + f.dpdx,
+            rx_direction: reflect(d.rx_direction, self.normalv),
+            ry_origin: self.over_point + f.dpdy,
+            ry_direction: reflect(d.ry_direction, self.normalv),
+        })
+    }
+
+    /// Differentials for the refracted ray, or `None` if either neighbour is
+    /// totally internally reflected.
+    pub fn refracted_differentials(&self, n_ratio: f64) -> Option<Differentials> {
+        let (d, f) = (self.differentials?, self.footprint?);
+        Some(Differentials {
+            rx_origin: self.under_point + f.dpdx,
+            rx_direction: refract(-d.rx_direction.normalize(), self.normalv, n_ratio)?,
+            ry_origin: self.under_point + f.dpdy,
+            ry_direction: refract(-d.ry_direction.normalize(), self.normalv, n_ratio)?,
+        })
+    }
 }
 
 pub fn prepare_computations(hit: &Intersection, r: &Ray, xs: &Vec<Intersection>) -> Computations {
@@ -206,16 +461,22 @@ pub fn prepare_computations(hit: &Intersection, r: &Ray, xs: &Vec<Intersection>)
 
     let point = r.position(t);
     let eyev = -r.direction;
-    let normalv = object.borrow().normal_at(point);
+    let geometric = object.borrow().normal_at(point);
+    let normalv = match &object.borrow().get_material().bump {
+        Some(bump) => bump.perturb(&*object.borrow(), &point, &geometric),
+        None => geometric,
+    };
     let reflectv = reflect(r.direction, normalv);
-    let (inside, normalv) = if normalv.dot(eyev) < 0.0 {
-        (true, -normalv)
+    let inside = geometric.dot(eyev) < 0.0;
+    let (geometric, normalv) = if inside {
+        (-geometric, -normalv)
     } else {
-        (false, normalv)
+        (geometric, normalv)
     };
 
-    let over_point = point + normalv * EPSILON;
-    let under_point = point - normalv * EPSILON;
+    // offset along the true surface so bumps cannot cause acne
+    let over_point = point + geometric * EPSILON;
+    let under_point = point - geometric * EPSILON;
 
     let mut containers = Vec::<Rc<RefCell<dyn Shape>>>::new();
     let mut n1 = 1.0;
@@ -255,7 +516,7 @@ pub fn prepare_computations(hit: &Intersection, r: &Ray, xs: &Vec<Intersection>)
         }
     }
 
-    Computations::new(
+    let mut comps = Computations::new(
         t,
         object,
         point,
@@ -267,7 +528,29 @@ pub fn prepare_computations(hit: &Intersection, r: &Ray, xs: &Vec<Intersection>)
         under_point,
         n1,
         n2,
-    )
+    );
+    comps.differentials = r.differentials;
+    comps.footprint = r
+        .differentials
+        .and_then(|d| footprint(&d, &point, &geometric));
+    comps
+}
+
+/// Where the differential rays cross the tangent plane at `point`, relative
+/// to `point`.
+fn footprint(d: &Differentials, point: &Point, normalv: &Vector) -> Option<Footprint> {
+    let offset = |origin: Point, direction: Vector| {
+        let denom = normalv.dot(direction);
+        if denom.abs() < EPSILON {
+            return None;
+        }
+        let t = normalv.dot(*point - origin) / denom;
+        Some(origin + direction * t - *point)
+    };
+    Some(Footprint {
+        dpdx: offset(d.rx_origin, d.rx_direction)?,
+        dpdy: offset(d.ry_origin, d.ry_direction)?,
+    })
 }
 
 #[cfg(test)]
@@ -277,11 +560,15 @@ mod tests {
         f64::consts::{FRAC_1_SQRT_2, SQRT_2},
     };
 
+    use float_cmp::approx_eq;
+
     use crate::{
-        lights::PointLight,
-        materials::Material,
+        canvas::Canvas,
+        lights::{EnvironmentLight, PointLight},
+        materials::{bump::Waves, layer::Layer, Material, ThinFilm},
         matrix::Matrix,
-        ray::{intersections, Intersection, Ray},
+        pattern::{scalar::Channel, ScalarTexture, Stripe},
+        ray::{intersections, Differentials, Intersection, Ray},
         shape::{Plane, Sphere},
         transformations::Transformation,
         tuple::{Color, Point, Tuple, Vector},
@@ -401,6 +688,161 @@ mod tests {
         let c = w.color_at(&r, DEFAULT_REFLECTION_COUNT);
         assert_eq!(c, Color::BLACK);
     }
+
+    #[test]
+    fn reflective_map_limits_reflection_to_stripes() {
+        let mut w = World::default();
+        let mut shape = Plane::new(2);
+        shape.material.reflective = 0.5;
+        shape.material.reflective_map = Some(
+            ScalarTexture::new(
+                Box::new(Stripe::new(Color::WHITE, Color::BLACK)),
+                Channel::Red,
+            )
+            .with_range(0.0, 0.5),
+        );
+        shape.transform = Matrix::default().translation(0.0, -1.0, 0.0);
+        let shape = Rc::new(RefCell::new(shape));
+        w.objects.push(shape.clone());
+        let r = Ray::new(
+            Point::new(-0.5, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(SQRT_2, shape.clone());
+        let comps = prepare_computations(&i, &r, &vec![]);
+        // the ray lands at (-0.5, -1, -2), in a black stripe
+        assert_eq!(
+            w.reflected_color(&comps, DEFAULT_REFLECTION_COUNT),
+            Color::BLACK
+        );
+
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let comps = prepare_computations(&i, &r, &vec![]);
+        assert_eq!(
+            w.reflected_color(&comps, DEFAULT_REFLECTION_COUNT),
+            Color::new(0.19032, 0.2379, 0.14274)
+        );
+    }
+
+    fn white_environment() -> EnvironmentLight {
+        let mut image = Canvas::new(16, 8);
+        image.pixels = vec![Color::WHITE; 16 * 8];
+        let mut env = EnvironmentLight::new(image);
+        env.samples = 1024;
+        env
+    }
+
+    #[test]
+    fn uniform_environment_lights_white_diffuse_surface_fully() {
+        let mut w = World::new();
+        w.environment = Some(white_environment());
+        let mut shape = Sphere::new(0);
+        shape.material.ambient = 0.0;
+        shape.material.diffuse = 1.0;
+        shape.material.specular = 0.0;
+        shape.material.color = Color::WHITE;
+        let shape = Rc::new(RefCell::new(shape));
+        w.objects.push(shape.clone());
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let c = w.color_at(&r, DEFAULT_REFLECTION_COUNT);
+        assert!(approx_eq!(f64, c.red, 1.0, epsilon = 0.05));
+        assert!(approx_eq!(f64, c.green, 1.0, epsilon = 0.05));
+        assert!(approx_eq!(f64, c.blue, 1.0, epsilon = 0.05));
+    }
+
+    #[test]
+    fn environment_light_is_shadowed() {
+        let mut w = World::new();
+        w.environment = Some(white_environment());
+        let floor = Plane::new(0);
+        let mut ceiling = Plane::new(1);
+        ceiling.transform = Matrix::default().translation(0.0, 1.0, 0.0);
+        w.objects = vec![Rc::new(RefCell::new(floor)), Rc::new(RefCell::new(ceiling))];
+        let r = Ray::new(Point::new(0.0, 0.5, 0.0), Vector::new(0.0, -1.0, 0.0));
+        assert_eq!(w.color_at(&r, DEFAULT_REFLECTION_COUNT), Color::BLACK);
+    }
+
+    #[test]
+    fn brighter_side_of_environment_lights_facing_surface() {
+        let mut image = Canvas::new(16, 8);
+        for y in 0..8 {
+            for x in 0..16 {
+                // the right half of the image covers +x
+                if x >= 8 {
+                    image.write(x, y, Color::WHITE);
+                }
+            }
+        }
+        let mut w = World::new();
+        w.environment = Some(EnvironmentLight::new(image));
+        let mut shape = Sphere::new(0);
+        shape.material.ambient = 0.0;
+        let shape = Rc::new(RefCell::new(shape));
+        w.objects.push(shape.clone());
+        let lit = w.color_at(
+            &Ray::new(Point::new(5.0, 0.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+            DEFAULT_REFLECTION_COUNT,
+        );
+        let dark = w.color_at(
+            &Ray::new(Point::new(-5.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)),
+            DEFAULT_REFLECTION_COUNT,
+        );
+        assert!(lit.red > 0.5);
+        assert_eq!(dark, Color::BLACK);
+    }
+
+    #[test]
+    fn mirror_lit_only_by_environment_reflects_background() {
+        let mut w = World::new();
+        w.background = Background::Color(Color::new(0.2, 0.4, 0.6));
+        let mut env = white_environment();
+        env.samples = 16;
+        w.environment = Some(env);
+        let mut shape = Sphere::new(0);
+        shape.material.ambient = 0.0;
+        shape.material.diffuse = 0.0;
+        shape.material.specular = 0.0;
+        shape.material.reflective = 1.0;
+        w.objects.push(Rc::new(RefCell::new(shape)));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(
+            w.color_at(&r, DEFAULT_REFLECTION_COUNT),
+            Color::new(0.2, 0.4, 0.6)
+        );
+    }
+
+    #[test]
+    fn color_when_ray_misses_is_background() {
+        let mut w = World::default();
+        w.background = Background::Gradient {
+            bottom: Color::BLACK,
+            top: Color::new(0.2, 0.4, 0.8),
+        };
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 2.0, 0.0));
+        let c = w.color_at(&r, DEFAULT_REFLECTION_COUNT);
+        assert_eq!(c, Color::new(0.2, 0.4, 0.8));
+    }
+
+    #[test]
+    fn reflection_that_escapes_sees_background() {
+        let mut w = World::new();
+        w.background = Background::Color(Color::new(0.2, 0.4, 0.6));
+        let mut shape = Plane::new(0);
+        shape.material.reflective = 0.5;
+        let shape = Rc::new(RefCell::new(shape));
+        w.objects.push(shape.clone());
+        let r = Ray::new(
+            Point::new(0.0, 1.0, -1.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(std::f64::consts::SQRT_2, shape.clone());
+        let comps = prepare_computations(&i, &r, &vec![]);
+        let color = w.reflected_color(&comps, DEFAULT_REFLECTION_COUNT);
+        assert_eq!(color, Color::new(0.1, 0.2, 0.3));
+    }
     #[test]
     fn color_when_ray_hits() {
         let w = World::default();
@@ -488,6 +930,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bump_changes_shading_normal_but_not_offset() {
+        let mut shape = Plane::new(0);
+        shape.material.bump = Some(Box::new(Waves::new(0.5, std::f64::consts::PI)));
+        let r = Ray::new(Point::new(0.0, 1.0, 0.5), Vector::new(0.0, -1.0, 0.0));
+        let i = Intersection::new(1.0, Rc::new(RefCell::new(shape)));
+        let comps = prepare_computations(&i, &r, &vec![]);
+        assert!(comps.normalv.x < 0.0);
+        assert_eq!(comps.reflectv, reflect(r.direction, comps.normalv));
+        assert_eq!(comps.over_point, Point::new(0.0, EPSILON, 0.5));
+    }
+
     #[test]
     fn reflection_for_nonreflective_material() {
         let w = World::default();
@@ -706,4 +1160,375 @@ mod tests {
         let color = w.shade_hit(&comps, 5);
         assert_eq!(color, Color::new(1.29609, 0.69643, 0.69243));
     }
+
+    #[test]
+    fn emissive_surface_glows_without_lights() {
+        let mut w = World::default();
+        w.lights = vec![];
+        let shape = w.objects[0].clone();
+        shape.borrow_mut().get_mut_material().emission = Color::new(1.0, 0.5, 0.0);
+        shape.borrow_mut().get_mut_material().emission_strength = 2.0;
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let c = w.color_at(&r, DEFAULT_REFLECTION_COUNT);
+        assert_eq!(c, Color::new(2.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn emissive_surface_glows_in_shadow() {
+        let mut w = World::default();
+        w.lights = vec![PointLight::new(Point::new(0.0, 0.0, -10.0), Color::WHITE)];
+        let s1 = Sphere::new(2);
+        w.objects.push(Rc::new(RefCell::new(s1)));
+        let mut s2 = Sphere::new(3);
+        s2.transform = Matrix::<4>::IDENTITY.translation(0.0, 0.0, 10.0);
+        s2.material.emission = Color::new(0.5, 0.5, 0.5);
+        w.objects.push(Rc::new(RefCell::new(s2)));
+        let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::new(4.0, w.objects[3].clone());
+        let comps = prepare_computations(&i, &r, &vec![]);
+        let c = w.shade_hit(&comps, DEFAULT_REFLECTION_COUNT);
+        assert_eq!(c, Color::new(0.6, 0.6, 0.6));
+    }
+
+    #[test]
+    fn rough_reflection_of_uniform_surroundings_matches_mirror() {
+        let mut w = World::new();
+        w.lights = vec![PointLight::new(Point::new(0.0, 5.0, 0.0), Color::WHITE)];
+        let mut sky = Sphere::new(0);
+        sky.transform = Matrix::default().scaling(10.0, 10.0, 10.0);
+        sky.material.ambient = 1.0;
+        sky.material.diffuse = 0.0;
+        sky.material.specular = 0.0;
+        w.objects.push(Rc::new(RefCell::new(sky)));
+        let mut shape = Plane::new(1);
+        shape.material.reflective = 0.5;
+        shape.material.roughness = 0.6;
+        shape.transform = Matrix::default().translation(0.0, -1.0, 0.0);
+        let shape = Rc::new(RefCell::new(shape));
+        w.objects.push(shape.clone());
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(SQRT_2, shape);
+        let comps = prepare_computations(&i, &r, &vec![]);
+        let color = w.reflected_color(&comps, DEFAULT_REFLECTION_COUNT);
+        assert_eq!(color, Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn rough_reflection_blurs_the_mirror_image() {
+        let mut w = World::new();
+        w.lights = vec![PointLight::new(Point::new(0.0, 5.0, 0.0), Color::WHITE)];
+        // black for x < 0 and white for x >= 0 inside a large sphere
+        let mut sky = Sphere::new(0);
+        sky.transform = Matrix::default().scaling(10.0, 10.0, 10.0);
+        sky.material.ambient = 1.0;
+        sky.material.diffuse = 0.0;
+        sky.material.specular = 0.0;
+        sky.material.pattern = Some(Box::new(Stripe::new(Color::WHITE, Color::BLACK)));
+        w.objects.push(Rc::new(RefCell::new(sky)));
+        let mut mirror = Plane::new(1);
+        mirror.material.reflective = 1.0;
+        mirror.material.roughness = 0.5;
+        mirror.transform = Matrix::default().translation(0.0, -1.0, 0.0);
+        let mirror = Rc::new(RefCell::new(mirror));
+        w.objects.push(mirror.clone());
+        // looking straight down, so the mirror image is the stripe edge
+        let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let i = Intersection::new(1.0, mirror);
+        let comps = prepare_computations(&i, &r, &vec![]);
+        let color = w.reflected_color(&comps, DEFAULT_REFLECTION_COUNT);
+        assert!(approx_eq!(f64, color.red, 0.5, epsilon = 0.1));
+        assert_eq!(color.red, color.green);
+    }
+
+    #[test]
+    fn glossy_sample_count_saturates_for_deep_recursion() {
+        assert_eq!(glossy_samples(1), 1);
+        assert_eq!(glossy_samples(2), 4);
+        assert_eq!(glossy_samples(3), GLOSSY_SAMPLES);
+        assert_eq!(glossy_samples(40), GLOSSY_SAMPLES);
+    }
+
+    #[test]
+    fn frosted_refraction_of_uniform_surroundings() {
+        let mut w = World::new();
+        w.lights = vec![PointLight::new(Point::new(0.0, 5.0, 0.0), Color::WHITE)];
+        let mut sky = Sphere::new(0);
+        sky.transform = Matrix::default().scaling(10.0, 10.0, 10.0);
+        sky.material.ambient = 1.0;
+        sky.material.diffuse = 0.0;
+        sky.material.specular = 0.0;
+        w.objects.push(Rc::new(RefCell::new(sky)));
+        let mut pane = Plane::new(1);
+        pane.material.transparency = 0.5;
+        pane.material.refraction_roughness = 0.7;
+        pane.transform = Matrix::default().translation(0.0, -1.0, 0.0);
+        let pane = Rc::new(RefCell::new(pane));
+        w.objects.push(pane.clone());
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(SQRT_2, pane);
+        let comps = prepare_computations(&i, &r, &vec![]);
+        let color = w.refracted_color(&comps, DEFAULT_REFLECTION_COUNT);
+        assert_eq!(color, Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn frosted_refraction_scatters_the_refracted_ray() {
+        let w = World::default();
+        let a = w.objects[0].clone();
+        {
+            let mut am = a.borrow_mut();
+            am.get_mut_material().ambient = 1.0;
+            am.get_mut_material().pattern =
+                Some(Box::new(crate::pattern::tests::TestPattern::new()));
+        }
+
+        let b = w.objects[1].clone();
+        {
+            let mut bm = b.borrow_mut();
+            bm.get_mut_material().transparency = 1.0;
+            bm.get_mut_material().refractive_index = 1.5;
+            bm.get_mut_material().refraction_roughness = 0.5;
+        }
+
+        let r = Ray::new(Point::new(0.0, 0.0, 0.1), Vector::new(0.0, 1.0, 0.0));
+        let i1 = Intersection::new(-0.9899, a.clone());
+        let i2 = Intersection::new(-0.4899, b.clone());
+        let i3 = Intersection::new(0.4899, b.clone());
+        let i4 = Intersection::new(0.9899, a.clone());
+        let xs = intersections(vec![i1, i2, i3, i4]);
+        let comps = prepare_computations(&xs[2], &r, &xs);
+        let c = w.refracted_color(&comps, 5);
+        // scattered rays land in a cap around the sharp hit at (0, 0.99888, 0.04725)
+        assert_eq!(c, Color::new(0.0012, 0.93177, 0.05257));
+    }
+
+    #[test]
+    fn shade_hit_with_conductor_tints_reflection() {
+        let mut w = World::default();
+        let mut shape = Plane::new(2);
+        shape.material.reflective = 1.0;
+        shape.material.fresnel = Fresnel::GOLD;
+        shape.material.ambient = 0.0;
+        shape.material.diffuse = 0.0;
+        shape.material.specular = 0.0;
+        shape.transform = Matrix::default().translation(0.0, -1.0, 0.0);
+        let shape = Rc::new(RefCell::new(shape));
+        w.objects.push(shape.clone());
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(SQRT_2, shape);
+        let comps = prepare_computations(&i, &r, &vec![]);
+        let mirror = w.reflected_color(&comps, DEFAULT_REFLECTION_COUNT);
+        let color = w.shade_hit(&comps, DEFAULT_REFLECTION_COUNT);
+        let reflectance = conductor_fresnel(&comps, Fresnel::GOLD_ETA, Fresnel::GOLD_K);
+        assert_eq!(color, mirror * reflectance);
+        assert!(reflectance.red > reflectance.blue);
+    }
+
+    #[test]
+    fn conductor_without_reflective_shows_no_reflection() {
+        let mut w = World::default();
+        let mut shape = Plane::new(2);
+        shape.material.fresnel = Fresnel::GOLD;
+        shape.material.ambient = 0.0;
+        shape.material.diffuse = 0.0;
+        shape.material.specular = 0.0;
+        shape.transform = Matrix::default().translation(0.0, -1.0, 0.0);
+        let shape = Rc::new(RefCell::new(shape));
+        w.objects.push(shape.clone());
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(SQRT_2, shape);
+        let comps = prepare_computations(&i, &r, &vec![]);
+        assert_eq!(w.shade_hit(&comps, DEFAULT_REFLECTION_COUNT), Color::BLACK);
+    }
+
+    #[test]
+    fn shade_hit_with_coated_conductor_tints_reflection_through_the_film() {
+        let mut w = World::default();
+        let mut shape = Plane::new(2);
+        shape.material.reflective = 1.0;
+        shape.material.fresnel = Fresnel::GOLD;
+        shape.material.thin_film = Some(ThinFilm::new(250.0, 1.5));
+        shape.material.ambient = 0.0;
+        shape.material.diffuse = 0.0;
+        shape.material.specular = 0.0;
+        shape.transform = Matrix::default().translation(0.0, -1.0, 0.0);
+        let shape = Rc::new(RefCell::new(shape));
+        w.objects.push(shape.clone());
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(SQRT_2, shape);
+        let comps = prepare_computations(&i, &r, &vec![]);
+        let mirror = w.reflected_color(&comps, DEFAULT_REFLECTION_COUNT);
+        let color = w.shade_hit(&comps, DEFAULT_REFLECTION_COUNT);
+        let reflectance = coated_conductor_fresnel(
+            &comps,
+            ThinFilm::new(250.0, 1.5),
+            Fresnel::GOLD_ETA,
+            Fresnel::GOLD_K,
+        );
+        assert_eq!(color, mirror * reflectance);
+        assert_ne!(
+            reflectance,
+            conductor_fresnel(&comps, Fresnel::GOLD_ETA, Fresnel::GOLD_K)
+        );
+    }
+
+    #[test]
+    fn shade_hit_with_soap_film_tints_reflection_and_refraction() {
+        let mut w = World::default();
+        let mut bubble = Plane::new(2);
+        bubble.transform = bubble.transform.translation(0.0, -1.0, 0.0);
+        bubble.material.reflective = 1.0;
+        bubble.material.transparency = 1.0;
+        bubble.material.ambient = 0.0;
+        bubble.material.diffuse = 0.0;
+        bubble.material.specular = 0.0;
+        bubble.material.thin_film = Some(ThinFilm::new(510.0 / (4.0 * 1.33), 1.33));
+        let bubble = Rc::new(RefCell::new(bubble));
+        w.objects.push(bubble.clone());
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let xs = intersections(vec![Intersection::new(SQRT_2, bubble)]);
+        let comps = prepare_computations(&xs[0], &r, &xs);
+        let reflectance = thin_film_fresnel(&comps, ThinFilm::new(510.0 / (4.0 * 1.33), 1.33));
+        let expected = w.reflected_color(&comps, 5) * reflectance
+            + w.refracted_color(&comps, 5) * (Color::WHITE - reflectance);
+        assert_eq!(w.shade_hit(&comps, 5), expected);
+    }
+
+    #[test]
+    fn intersect_skips_cut_out_hits() {
+        let mut w = World::default();
+        let mut plane = Plane::new(2);
+        plane.material.opacity_map = Some(ScalarTexture::new(
+            Box::new(Stripe::new(Color::WHITE, Color::BLACK)),
+            Channel::Red,
+        ));
+        w.objects = vec![Rc::new(RefCell::new(plane))];
+        let down = Vector::new(0.0, -1.0, 0.0);
+        let solid = w.intersect(&Ray::new(Point::new(0.5, 1.0, 0.0), down));
+        assert_eq!(solid.len(), 1);
+        let hole = w.intersect(&Ray::new(Point::new(1.5, 1.0, 0.0), down));
+        assert!(hole.is_empty());
+    }
+
+    #[test]
+    fn light_passes_through_cut_out_hits() {
+        let mut w = World::default();
+        let mut plane = Plane::new(2);
+        plane.material.opacity_map = Some(ScalarTexture::new(
+            Box::new(Stripe::new(Color::WHITE, Color::BLACK)),
+            Channel::Red,
+        ));
+        w.objects = vec![Rc::new(RefCell::new(plane))];
+        let up = Vector::new(0.0, 1.0, 0.0);
+        assert!(w.is_occluded(&Point::new(0.5, -1.0, 0.0), &up, 5.0));
+        assert!(!w.is_occluded(&Point::new(1.5, -1.0, 0.0), &up, 5.0));
+    }
+
+    #[test]
+    fn layer_mixes_shading_of_both_materials() {
+        let w = World::default();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = w.objects[0].clone();
+        let i = Intersection::new(4.0, shape.clone());
+        let comps = prepare_computations(&i, &r, &vec![i.clone()]);
+        let base = w.shade_hit(&comps, DEFAULT_REFLECTION_COUNT);
+        let mut paint = Material::default();
+        paint.color = Color::new(1.0, 0.0, 0.0);
+        paint.reflective = 0.5;
+        shape.borrow_mut().get_mut_material().layer = Some(Box::new(Layer::new(paint, 1.0)));
+        let painted = w.shade_hit(&comps, DEFAULT_REFLECTION_COUNT);
+        if let Some(layer) = &mut shape.borrow_mut().get_mut_material().layer {
+            layer.weight = 0.25;
+        }
+        let c = w.shade_hit(&comps, DEFAULT_REFLECTION_COUNT);
+        assert_eq!(c, base * 0.75 + painted * 0.25);
+    }
+
+    #[test]
+    fn masked_layer_covers_base_where_mask_is_full() {
+        let w = World::default();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = w.objects[0].clone();
+        let i = Intersection::new(4.0, shape.clone());
+        let comps = prepare_computations(&i, &r, &vec![i.clone()]);
+        let mut paint = Material::default();
+        paint.color = Color::new(1.0, 0.0, 0.0);
+        paint.reflective = 0.5;
+        shape.borrow_mut().get_mut_material().layer = Some(Box::new(Layer::new(paint, 1.0)));
+        let painted = w.shade_hit(&comps, DEFAULT_REFLECTION_COUNT);
+        if let Some(layer) = &mut shape.borrow_mut().get_mut_material().layer {
+            layer.weight = 0.0;
+            layer.mask = Some(ScalarTexture::new(
+                Box::new(Stripe::new(Color::WHITE, Color::BLACK)),
+                Channel::Red,
+            ));
+        }
+        let c = w.shade_hit(&comps, DEFAULT_REFLECTION_COUNT);
+        assert_eq!(c, painted);
+    }
+
+    #[test]
+    fn footprint_spans_neighbouring_rays_on_the_surface() {
+        let plane: Rc<RefCell<dyn Shape>> = Rc::new(RefCell::new(Plane::new(0)));
+        let origin = Point::new(0.0, 1.0, 0.0);
+        let r =
+            Ray::new(origin, Vector::new(0.0, -1.0, 0.0)).with_differentials(Some(Differentials {
+                rx_origin: origin,
+                rx_direction: Vector::new(0.01, -1.0, 0.0),
+                ry_origin: origin,
+                ry_direction: Vector::new(0.0, -1.0, 0.01),
+            }));
+        let i = Intersection::new(1.0, plane);
+        let comps = prepare_computations(&i, &r, &vec![i.clone()]);
+        let footprint = comps.footprint.unwrap();
+        assert_eq!(footprint.dpdx, Vector::new(0.01, 0.0, 0.0));
+        assert_eq!(footprint.dpdy, Vector::new(0.0, 0.0, 0.01));
+    }
+
+    #[test]
+    fn no_footprint_without_differentials() {
+        let plane: Rc<RefCell<dyn Shape>> = Rc::new(RefCell::new(Plane::new(0)));
+        let r = Ray::new(Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let i = Intersection::new(1.0, plane);
+        let comps = prepare_computations(&i, &r, &vec![i.clone()]);
+        assert_eq!(comps.footprint, None);
+        assert_eq!(comps.reflected_differentials(), None);
+    }
+
+    #[test]
+    fn reflection_carries_differentials_onwards() {
+        let plane: Rc<RefCell<dyn Shape>> = Rc::new(RefCell::new(Plane::new(0)));
+        let origin = Point::new(0.0, 1.0, 0.0);
+        let r =
+            Ray::new(origin, Vector::new(0.0, -1.0, 0.0)).with_differentials(Some(Differentials {
+                rx_origin: origin,
+                rx_direction: Vector::new(0.01, -1.0, 0.0),
+                ry_origin: origin,
+                ry_direction: Vector::new(0.0, -1.0, 0.01),
+            }));
+        let i = Intersection::new(1.0, plane);
+        let comps = prepare_computations(&i, &r, &vec![i.clone()]);
+        let d = comps.reflected_differentials().unwrap();
+        assert_eq!(d.rx_origin, comps.over_point + Vector::new(0.01, 0.0, 0.0));
+        assert_eq!(d.rx_direction, Vector::new(0.01, 1.0, 0.0));
+        assert_eq!(d.ry_direction, Vector::new(0.0, 1.0, 0.01));
+    }
 }